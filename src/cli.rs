@@ -0,0 +1,165 @@
+use crate::diff::options::{ChangeSeverity, DiffOptions};
+use crate::pkg::types::TsVersionRange;
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: breakpoint <package path> [--ts-versions <range>] [--compare-assets] [--resolve-node-modules] [--value-changes <breaking | warning | ignore>] [--jobs <count>]
+       breakpoint check <package path> [--jobs <count>]
+       breakpoint pack-list <package path> [--compare-published]";
+
+/// The options each command accepts, as the others' would be silently ignored.
+const DIFF_FLAGS: &[&str] = &[
+    "--ts-versions",
+    "--compare-assets",
+    "--resolve-node-modules",
+    "--value-changes",
+    "--jobs",
+];
+const CHECK_FLAGS: &[&str] = &["--jobs"];
+const PACK_LIST_FLAGS: &[&str] = &["--compare-published"];
+
+pub enum Command {
+    /// Compares the package against its last published version.
+    Diff {
+        pkg_dir: PathBuf,
+        options: DiffOptions,
+    },
+    /// Compares the entries of the package against each other.
+    Check { pkg_dir: PathBuf, jobs: usize },
+    /// Lists the files the package would publish, and why.
+    PackList {
+        pkg_dir: PathBuf,
+        compare_published: bool,
+    },
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command> {
+    let mut args = args.iter().skip(1);
+    let mut positional_args: Vec<&String> = Vec::new();
+    let mut options = DiffOptions::default();
+    let mut compare_published = false;
+    let mut flags: Vec<&str> = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if arg.starts_with("--") {
+            flags.push(arg);
+        }
+
+        match arg.as_str() {
+            "--ts-versions" => {
+                let range = match args.next() {
+                    Some(range) => range,
+                    None => bail!("Expected a version range after '--ts-versions'.\n{USAGE}"),
+                };
+
+                options.ts_versions = TsVersionRange::parse(range)
+                    .with_context(|| "Failed to parse '--ts-versions' option.")?;
+            }
+            "--jobs" => {
+                let jobs = match args.next() {
+                    Some(jobs) => jobs,
+                    None => bail!("Expected a thread count after '--jobs'.\n{USAGE}"),
+                };
+
+                options.jobs = jobs
+                    .parse()
+                    .with_context(|| format!("Invalid '--jobs' thread count: {jobs}"))?;
+            }
+            "--value-changes" => {
+                let severity = match args.next() {
+                    Some(severity) => severity,
+                    None => bail!("Expected a severity after '--value-changes'.\n{USAGE}"),
+                };
+
+                options.value_change_severity = ChangeSeverity::parse(severity)
+                    .with_context(|| "Failed to parse '--value-changes' option.")?;
+            }
+            "--compare-assets" => options.compare_asset_contents = true,
+            "--compare-published" => compare_published = true,
+            "--resolve-node-modules" => options.resolve_node_modules = true,
+            flag if flag.starts_with("--") => bail!("Unknown option '{flag}'.\n{USAGE}"),
+            _ => positional_args.push(arg),
+        }
+    }
+
+    let (command, supported_flags) = match positional_args.as_slice() {
+        [command, pkg_dir] if command.as_str().eq("check") => (
+            Command::Check {
+                pkg_dir: PathBuf::from(pkg_dir),
+                jobs: options.jobs,
+            },
+            CHECK_FLAGS,
+        ),
+        [command, pkg_dir] if command.as_str().eq("pack-list") => (
+            Command::PackList {
+                pkg_dir: PathBuf::from(pkg_dir),
+                compare_published,
+            },
+            PACK_LIST_FLAGS,
+        ),
+        [pkg_dir] => (
+            Command::Diff {
+                pkg_dir: PathBuf::from(pkg_dir),
+                options,
+            },
+            DIFF_FLAGS,
+        ),
+        _ => bail!("Expected a package path.\n{USAGE}"),
+    };
+
+    if let Some(flag) = flags.iter().find(|flag| !supported_flags.contains(flag)) {
+        bail!("Option '{flag}' is not supported by this command.\n{USAGE}");
+    }
+
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command> {
+        let args: Vec<String> = ["breakpoint"]
+            .iter()
+            .chain(args)
+            .map(|arg| arg.to_string())
+            .collect();
+
+        parse_args(&args)
+    }
+
+    #[test]
+    fn commands_accept_their_own_options() {
+        assert!(matches!(
+            parse(&[
+                "pkg",
+                "--ts-versions",
+                ">=4.7",
+                "--compare-assets",
+                "--jobs",
+                "2"
+            ]),
+            Ok(Command::Diff { .. })
+        ));
+        assert!(matches!(
+            parse(&["check", "pkg", "--jobs", "2"]),
+            Ok(Command::Check { jobs: 2, .. })
+        ));
+        assert!(matches!(
+            parse(&["pack-list", "pkg", "--compare-published"]),
+            Ok(Command::PackList {
+                compare_published: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn options_of_other_commands_are_rejected() {
+        assert!(parse(&["check", "pkg", "--ts-versions", ">=4.7"]).is_err());
+        assert!(parse(&["check", "pkg", "--compare-assets"]).is_err());
+        assert!(parse(&["pack-list", "pkg", "--value-changes", "ignore"]).is_err());
+        assert!(parse(&["pack-list", "pkg", "--jobs", "2"]).is_err());
+        assert!(parse(&["pkg", "--compare-published"]).is_err());
+    }
+}
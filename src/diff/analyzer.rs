@@ -1,129 +1,1127 @@
-use crate::diff::assets::{diff_pkg_assets, diff_pkg_entries, diff_pkg_entry_exports};
-use crate::diff::results::{BreakType, BrokenEntryResult, BrokenExport, DiffResults};
+use crate::diff::assets::{
+    diff_pkg_asset_contents, diff_pkg_assets, diff_pkg_dependencies, diff_pkg_entries,
+    diff_pkg_entry_exports, diff_pkg_entry_global_names, diff_pkg_types, get_pkg_entry_exports,
+    PkgDependencyChanges,
+};
+use crate::diff::jobs::run_jobs;
+use crate::diff::options::{ChangeSeverity, DiffOptions};
+use crate::diff::results::{
+    BreakType, BrokenEntryResult, BrokenExport, DiffResults, MovedEntry, PackListDiffResults,
+};
+use crate::ecma::docs::{DocTags, ReleaseTag};
+use crate::ecma::entity::{EntityDeclaration, EntityKind};
+use crate::ecma::positions::TypePosition;
+use crate::ecma::types::{FunctionType, TypeDeclaration, TypeRepr};
+use crate::ecma::value::{get_enum_member_values, ConstantValue};
 use crate::pkg::contents::PkgContents;
 use crate::pkg::entries::{PkgEntry, PkgEntryType};
 use crate::pkg::Pkg;
 use anyhow::{Context, Result};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use swc_ecma_ast::TsEnumDecl;
 
-pub fn get_diff_between(previous_pkg: Pkg, current_pkg: Pkg) -> Result<DiffResults> {
+type MatchingEntryJob<'pkg> = (PkgEntryType, &'pkg String, &'pkg PkgEntry, &'pkg PkgEntry);
+
+pub fn get_diff_between(
+    previous_pkg: Pkg,
+    current_pkg: Pkg,
+    options: &DiffOptions,
+) -> Result<DiffResults> {
     let mut diff_report = DiffResults::default();
 
     analyze_changes_between_contents(
         &mut diff_report,
         &previous_pkg.contents,
         &current_pkg.contents,
+        options,
     )
     .with_context(|| "Failed to count breaking changes between previous/current contents.")?;
 
-    analyze_changes_between_entries(
-        PkgEntryType::Main,
-        &mut diff_report,
-        &previous_pkg.entries.main,
-        &current_pkg.entries.main,
-    )
-    .with_context(|| "Failed to count breaking changes between previous/current main entries.")?;
+    let previous_entries = &previous_pkg.entries;
+    let current_entries = &current_pkg.entries;
 
-    analyze_changes_between_entries(
-        PkgEntryType::Browser,
-        &mut diff_report,
-        &previous_pkg.entries.browser,
-        &current_pkg.entries.browser,
-    )
-    .with_context(|| {
-        "Failed to count breaking changes between previous/current browser entries."
-    })?;
+    let entry_fields = [
+        (
+            PkgEntryType::Main,
+            &previous_entries.main,
+            &current_entries.main,
+        ),
+        (
+            PkgEntryType::Module,
+            &previous_entries.module,
+            &current_entries.module,
+        ),
+        (
+            PkgEntryType::JsNextMain,
+            &previous_entries.jsnext_main,
+            &current_entries.jsnext_main,
+        ),
+        (
+            PkgEntryType::Unpkg,
+            &previous_entries.unpkg,
+            &current_entries.unpkg,
+        ),
+        (
+            PkgEntryType::Jsdelivr,
+            &previous_entries.jsdelivr,
+            &current_entries.jsdelivr,
+        ),
+        (
+            PkgEntryType::Browser,
+            &previous_entries.browser,
+            &current_entries.browser,
+        ),
+        (
+            PkgEntryType::Exports,
+            &previous_entries.exports,
+            &current_entries.exports,
+        ),
+    ];
 
-    analyze_changes_between_entries(
-        PkgEntryType::Exports,
-        &mut diff_report,
-        &previous_pkg.entries.exports,
-        &current_pkg.entries.exports,
-    )
-    .with_context(|| {
-        "Failed to count breaking changes between previous/current exports entries."
+    let dependency_changes = diff_pkg_dependencies(&previous_pkg, &current_pkg);
+    let types_entries = get_matching_types_entries(&previous_pkg, &current_pkg, options)
+        .with_context(|| "Failed to resolve previous/current types entries.")?;
+
+    let mut matching_entry_jobs = Vec::new();
+
+    for (entry_type, previous_entries, current_entries) in entry_fields {
+        analyze_changes_between_entries(
+            &entry_type,
+            &mut diff_report,
+            &mut matching_entry_jobs,
+            previous_entries,
+            current_entries,
+        )
+        .with_context(|| {
+            format!(
+                "Failed to count breaking changes between previous/current {entry_type} entries."
+            )
+        })?;
+    }
+
+    for (subpath, previous_entry, current_entry) in types_entries.iter() {
+        matching_entry_jobs.push((PkgEntryType::Types, subpath, previous_entry, current_entry));
+    }
+
+    // Matching entries are parsed and walked in parallel, as that is where most of the
+    // analysis time goes.
+    let matching_entry_results = run_jobs(&matching_entry_jobs, options.jobs, |job| {
+        let (entry_type, entry_name, previous_entry, current_entry) = job;
+
+        analyze_changes_between_matching_entries(
+            entry_type,
+            entry_name,
+            previous_entry,
+            current_entry,
+            &dependency_changes,
+            options,
+        )
+        .with_context(|| {
+            format!("Failed to count breaking changes between previous/current {entry_type} entry: {entry_name}")
+        })
     })?;
 
+    diff_report.broken_entries.extend(matching_entry_results);
+
+    diff_report.broken_entries.sort_by(|entry_a, entry_b| {
+        (&entry_a.kind, &entry_a.name).cmp(&(&entry_b.kind, &entry_b.name))
+    });
+
+    diff_report.export_cycles = current_pkg.contents.module_cache().get_cycles();
+
+    diff_report.broken_types = diff_pkg_types(
+        &previous_pkg.entries.types,
+        &current_pkg.entries.types,
+        &options.ts_versions,
+    );
+
     Ok(diff_report)
 }
 
+/// Compares the files of two package versions, regardless of their contents.
+pub fn get_pack_list_diff_between(
+    previous_pkg: &Pkg,
+    current_pkg: &Pkg,
+) -> Result<PackListDiffResults> {
+    let previous_files = previous_pkg
+        .contents
+        .file_list()
+        .with_context(|| "Failed to list files of previous package.")?;
+
+    let current_files = current_pkg
+        .contents
+        .file_list()
+        .with_context(|| "Failed to list files of current package.")?;
+
+    let mut added_files: Vec<PathBuf> =
+        current_files.difference(&previous_files).cloned().collect();
+
+    let mut removed_files: Vec<PathBuf> =
+        previous_files.difference(&current_files).cloned().collect();
+
+    added_files.sort();
+    removed_files.sort();
+
+    Ok(PackListDiffResults {
+        added_files,
+        removed_files,
+    })
+}
+
 fn analyze_changes_between_contents(
     diff_results: &mut DiffResults,
     previous_contents: &PkgContents,
     current_contents: &PkgContents,
+    options: &DiffOptions,
 ) -> Result<()> {
-    diff_results.removed_assets = diff_pkg_assets(previous_contents, current_contents)?;
+    let (removed_assets, moved_assets) = diff_pkg_assets(previous_contents, current_contents)?;
+
+    diff_results.removed_assets = removed_assets;
+    diff_results.moved_assets = moved_assets;
+
+    if options.compare_asset_contents {
+        diff_results.modified_assets =
+            diff_pkg_asset_contents(previous_contents, current_contents, options)
+                .with_context(|| "Failed to compare contents of previous/current assets.")?;
+    }
+
     Ok(())
 }
 
-fn analyze_changes_between_entries(
-    entry_type: PkgEntryType,
+fn analyze_changes_between_entries<'pkg>(
+    entry_type: &PkgEntryType,
     diff_results: &mut DiffResults,
-    previous_entries: &HashMap<String, PkgEntry>,
-    current_entries: &HashMap<String, PkgEntry>,
+    matching_entry_jobs: &mut Vec<MatchingEntryJob<'pkg>>,
+    previous_entries: &'pkg HashMap<String, PkgEntry>,
+    current_entries: &'pkg HashMap<String, PkgEntry>,
 ) -> Result<()> {
-    let (missing_entries, matching_entries) =
+    let (missing_entries, matching_entries, moved_entries) =
         diff_pkg_entries(previous_entries, current_entries)
             .with_context(|| "Failed to analyze diff between previous & current entries.")?;
 
     for missing_entry_name in missing_entries {
+        let previous_entry = &previous_entries[missing_entry_name];
+
+        let moved_to = moved_entries.get(missing_entry_name).map(|current_entry| {
+            // Only subpaths can be kept working through an exports alias.
+            let exports_alias = match (entry_type, &previous_entry.subpath) {
+                (PkgEntryType::Exports, Some(subpath)) => Some((
+                    subpath.to_owned(),
+                    format!(
+                        "./{}",
+                        current_entry
+                            .path
+                            .strip_prefix("./")
+                            .unwrap_or(&current_entry.path)
+                            .display()
+                    ),
+                )),
+                _ => None,
+            };
+
+            MovedEntry {
+                name: current_entry.name.to_owned(),
+                path: current_entry.path.to_owned(),
+                exports_alias,
+            }
+        });
+
         diff_results.broken_entries.push(BrokenEntryResult {
             is_missing: true,
             kind: entry_type.clone(),
             name: missing_entry_name.to_owned(),
+            retargeted: None,
+            moved_to,
             broken_exports: Vec::new(),
         });
     }
 
-    for matching_entry in matching_entries {
-        let (entry_name, entries) = matching_entry;
-        let (previous_entry, current_entry) = entries;
+    for (entry_name, (previous_entry, current_entry)) in matching_entries {
+        matching_entry_jobs.push((
+            entry_type.clone(),
+            entry_name,
+            previous_entry,
+            current_entry,
+        ));
+    }
 
-        let broken_exports = analyze_changes_between_entry_exports(previous_entry, current_entry)
-            .with_context(|| {
-            format!("Failed to analyze export diff between previous & current entry: {entry_name}")
-        })?;
+    Ok(())
+}
 
-        diff_results.broken_entries.push(BrokenEntryResult {
-            is_missing: false,
-            kind: entry_type.clone(),
-            name: entry_name.to_owned(),
-            broken_exports,
-        });
+/// The declaration files of each subpath that has types in both versions, as loaded by the
+/// latest TypeScript version in range, preferring bundler resolution.
+fn get_matching_types_entries(
+    previous_pkg: &Pkg,
+    current_pkg: &Pkg,
+    options: &DiffOptions,
+) -> Result<Vec<(String, PkgEntry, PkgEntry)>> {
+    let mut types_entries = Vec::new();
+
+    for (subpath, previous_types) in previous_pkg.entries.types.iter() {
+        let Some(current_types) = current_pkg.entries.types.get(subpath) else {
+            continue;
+        };
+
+        let resolved_paths = previous_types
+            .resolutions
+            .iter()
+            .rev()
+            .filter(|((_, version), _)| options.ts_versions.matches(version))
+            .find_map(|(key, previous_resolution)| {
+                let current_path = current_types.resolutions.get(key)?.path.as_ref()?;
+
+                Some((previous_resolution.path.as_ref()?, current_path))
+            });
+
+        let Some((previous_path, current_path)) = resolved_paths else {
+            continue;
+        };
+
+        let previous_entry = PkgEntry::new(
+            subpath.to_owned(),
+            previous_path.to_owned(),
+            Some(subpath.to_owned()),
+            Arc::clone(&previous_pkg.contents),
+        )
+        .with_context(|| format!("Failed to load previous types of subpath '{subpath}'."))?;
+
+        let current_entry = PkgEntry::new(
+            subpath.to_owned(),
+            current_path.to_owned(),
+            Some(subpath.to_owned()),
+            Arc::clone(&current_pkg.contents),
+        )
+        .with_context(|| format!("Failed to load current types of subpath '{subpath}'."))?;
+
+        types_entries.push((subpath.to_owned(), previous_entry, current_entry));
     }
 
-    Ok(())
+    Ok(types_entries)
+}
+
+fn analyze_changes_between_matching_entries(
+    entry_type: &PkgEntryType,
+    entry_name: &str,
+    previous_entry: &PkgEntry,
+    current_entry: &PkgEntry,
+    dependency_changes: &PkgDependencyChanges,
+    options: &DiffOptions,
+) -> Result<BrokenEntryResult> {
+    let broken_exports = analyze_changes_between_entry_exports(
+        previous_entry,
+        current_entry,
+        dependency_changes,
+        options,
+    )
+    .with_context(|| "Failed to analyze export diff between previous & current entry.")?;
+
+    let retargeted = if previous_entry.path.ne(&current_entry.path) {
+        Some((
+            previous_entry.path.to_owned(),
+            current_entry.path.to_owned(),
+        ))
+    } else {
+        None
+    };
+
+    Ok(BrokenEntryResult {
+        is_missing: false,
+        kind: entry_type.clone(),
+        name: entry_name.to_owned(),
+        retargeted,
+        moved_to: None,
+        broken_exports,
+    })
 }
 
 fn analyze_changes_between_entry_exports(
     previous_entry: &PkgEntry,
     current_entry: &PkgEntry,
+    dependency_changes: &PkgDependencyChanges,
+    options: &DiffOptions,
 ) -> Result<Vec<BrokenExport>> {
     let mut broken_exports: Vec<BrokenExport> = Vec::new();
 
     let (
         is_default_export_missing,
         matching_default_export,
-        missing_named_exports,
+        mut missing_named_exports,
+        ambiguous_named_exports,
         matching_named_exports,
-    ) = diff_pkg_entry_exports(previous_entry, current_entry)?;
+        potentially_broken_exports,
+        type_positions,
+    ) = diff_pkg_entry_exports(
+        previous_entry,
+        current_entry,
+        dependency_changes,
+        options.resolve_node_modules,
+    )?;
+
+    // Consumers relied on the doc tags of the previous version.
+    let previous_doc_tags = &get_pkg_entry_exports(previous_entry, false)?.doc_tags;
 
     if is_default_export_missing {
-        broken_exports.push((String::from("Default export"), BreakType::Removed));
-    } else if matching_default_export.is_some() {
-        todo!("handle matching default export breaking diff analysis")
+        broken_exports.extend(get_removed_export_breaks(
+            String::from("Default export"),
+            BreakType::Removed,
+            previous_doc_tags.get("default"),
+        ));
     }
 
-    for missing_export_name in missing_named_exports {
+    let missing_global_name =
+        diff_pkg_entry_global_names(previous_entry, current_entry, options.resolve_node_modules)?;
+
+    if let Some(global_name) = missing_global_name {
         broken_exports.push((
+            format!("UMD global '{global_name}'"),
+            BreakType::RemovedOrRenamed,
+        ));
+    }
+
+    if let Some((previous_default_export, current_default_export)) = matching_default_export {
+        let default_export_breaks = analyze_changes_between_declarations(
+            ("Default export", "the default export"),
+            true,
+            &previous_default_export,
+            &current_default_export,
+            &type_positions,
+            options.value_change_severity,
+        );
+
+        broken_exports.extend(apply_release_tag(
+            default_export_breaks,
+            previous_doc_tags.get("default"),
+        ));
+    }
+
+    let mut matching_named_exports: Vec<_> = matching_named_exports.into_iter().collect();
+    matching_named_exports.sort_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b));
+
+    for (export_name, (previous_export, current_export)) in matching_named_exports {
+        let named_export_breaks = analyze_changes_between_declarations(
+            (
+                &format!("Named export '{export_name}'"),
+                &format!("named export '{export_name}'"),
+            ),
+            false,
+            &previous_export,
+            &current_export,
+            &type_positions,
+            options.value_change_severity,
+        );
+
+        broken_exports.extend(apply_release_tag(
+            named_export_breaks,
+            previous_doc_tags.get(&export_name),
+        ));
+    }
+
+    missing_named_exports.sort();
+
+    for missing_export_name in missing_named_exports {
+        broken_exports.extend(get_removed_export_breaks(
             format!("Named export '{missing_export_name}'"),
             BreakType::RemovedOrRenamed,
+            previous_doc_tags.get(&missing_export_name),
         ));
     }
 
-    for _named_export in matching_named_exports {
-        todo!("handle matching named export breaking diff analysis")
+    for (ambiguous_export_name, (first, second)) in ambiguous_named_exports {
+        let ambiguous_export_break = (
+            format!("Named export '{ambiguous_export_name}'"),
+            BreakType::BecameAmbiguous { first, second },
+        );
+
+        broken_exports.extend(apply_release_tag(
+            vec![ambiguous_export_break],
+            previous_doc_tags.get(&ambiguous_export_name),
+        ));
     }
 
+    // Potential breaks are kept with the others, and told apart by their break type.
+    broken_exports.extend(potentially_broken_exports);
+
     Ok(broken_exports)
 }
+
+/// Removing an export is expected to be announced by deprecating it in an earlier release.
+fn get_removed_export_breaks(
+    label: String,
+    break_type: BreakType,
+    doc_tags: Option<&DocTags>,
+) -> Vec<BrokenExport> {
+    let is_deprecated = doc_tags.is_some_and(|doc_tags| doc_tags.is_deprecated);
+
+    let break_type = if is_deprecated {
+        break_type
+    } else {
+        BreakType::WithoutDeprecation {
+            change: Box::new(break_type),
+        }
+    };
+
+    apply_release_tag(vec![(label, break_type)], doc_tags)
+}
+
+/// Internal exports are not part of the API, so their breaks are left out, while those of
+/// pre-release exports are downgraded to potential breaks.
+fn apply_release_tag(
+    broken_exports: Vec<BrokenExport>,
+    doc_tags: Option<&DocTags>,
+) -> Vec<BrokenExport> {
+    let release_tag = doc_tags
+        .and_then(|doc_tags| doc_tags.release_tag)
+        .unwrap_or_default();
+
+    match release_tag {
+        ReleaseTag::Public => broken_exports,
+        ReleaseTag::Internal => Vec::new(),
+        ReleaseTag::Beta | ReleaseTag::Alpha => broken_exports
+            .into_iter()
+            .map(|(label, break_type)| {
+                let break_type = BreakType::PreRelease {
+                    change: Box::new(break_type),
+                    release_tag,
+                };
+
+                (label, break_type)
+            })
+            .collect(),
+    }
+}
+
+fn analyze_changes_between_declarations(
+    labels: (&str, &str),
+    is_default_export: bool,
+    previous_declaration: &EntityDeclaration,
+    current_declaration: &EntityDeclaration,
+    type_positions: &HashMap<String, TypePosition>,
+    value_change_severity: ChangeSeverity,
+) -> Vec<BrokenExport> {
+    let previous_kind = previous_declaration.kind();
+    let current_kind = current_declaration.kind();
+
+    // A computed export may well be of the same kind, so only known kinds are compared.
+    let is_kind_known =
+        previous_kind.ne(&EntityKind::Unknown) && current_kind.ne(&EntityKind::Unknown);
+
+    if is_kind_known && previous_kind.ne(&current_kind) {
+        return vec![(
+            labels.0.into(),
+            BreakType::KindChanged {
+                from: previous_kind,
+                to: current_kind,
+            },
+        )];
+    }
+
+    if let (Some(previous_type), Some(current_type)) = (
+        previous_declaration.type_declaration(),
+        current_declaration.type_declaration(),
+    ) {
+        // Types not used by any export may be used any way by consumers.
+        let type_position = type_positions
+            .get(previous_declaration.name())
+            .copied()
+            .unwrap_or(TypePosition::BOTH);
+
+        return analyze_changes_between_types(labels, &previous_type, &current_type, type_position);
+    }
+
+    let is_overloaded =
+        |declaration: &EntityDeclaration| matches!(declaration, EntityDeclaration::Overloads(..));
+
+    if is_overloaded(previous_declaration) || is_overloaded(current_declaration) {
+        return analyze_changes_between_overloads(
+            labels.1,
+            &previous_declaration.signatures(),
+            &current_declaration.signatures(),
+        );
+    }
+
+    if let (EntityDeclaration::Enum(_, previous_enum), EntityDeclaration::Enum(_, current_enum)) =
+        (previous_declaration, current_declaration)
+    {
+        return analyze_changes_between_enum_members(labels.1, previous_enum, current_enum);
+    }
+
+    analyze_changes_between_values(
+        labels,
+        is_default_export,
+        previous_declaration,
+        current_declaration,
+        value_change_severity,
+    )
+}
+
+/// Compares the literal values of matching exports. Removed properties are breaking for
+/// frozen objects & default exports, as those are used like namespaces, and are value
+/// changes to `undefined` otherwise.
+fn analyze_changes_between_values(
+    (export_label, property_label): (&str, &str),
+    is_default_export: bool,
+    previous_declaration: &EntityDeclaration,
+    current_declaration: &EntityDeclaration,
+    value_change_severity: ChangeSeverity,
+) -> Vec<BrokenExport> {
+    let mut broken_exports = Vec::new();
+
+    // Values that are not literals can't be compared.
+    let (Some(previous_value), Some(current_value)) = (
+        previous_declaration.constant_value(),
+        current_declaration.constant_value(),
+    ) else {
+        return broken_exports;
+    };
+
+    match (previous_value, current_value) {
+        (
+            ConstantValue::Object {
+                properties: previous_properties,
+                is_frozen,
+            },
+            ConstantValue::Object {
+                properties: current_properties,
+                ..
+            },
+        ) => {
+            for (key, previous_property) in previous_properties {
+                let label = format!("Property '{key}' of {property_label}");
+
+                match current_properties.get(&key) {
+                    None if is_frozen || is_default_export => {
+                        broken_exports.push((label, BreakType::RemovedOrRenamed))
+                    }
+                    None => broken_exports.extend(get_value_change(
+                        label,
+                        previous_property.unwrap_or("a computed value".into()),
+                        "undefined".into(),
+                        value_change_severity,
+                    )),
+                    Some(Some(current_property)) => {
+                        if let Some(previous_property) = previous_property {
+                            if previous_property.ne(current_property) {
+                                broken_exports.extend(get_value_change(
+                                    label,
+                                    previous_property,
+                                    current_property.to_owned(),
+                                    value_change_severity,
+                                ));
+                            }
+                        }
+                    }
+                    Some(None) => {}
+                }
+            }
+        }
+        (previous_value, current_value) => {
+            if previous_value.ne(&current_value) {
+                broken_exports.extend(get_value_change(
+                    export_label.into(),
+                    previous_value.to_string(),
+                    current_value.to_string(),
+                    value_change_severity,
+                ));
+            }
+        }
+    }
+
+    broken_exports
+}
+
+/// Compares interfaces & type aliases structurally. Types consumers provide can't narrow
+/// or gain required properties, and those they receive can't widen, while removals break
+/// both.
+fn analyze_changes_between_types(
+    (export_label, property_label): (&str, &str),
+    previous_type: &TypeDeclaration,
+    current_type: &TypeDeclaration,
+    type_position: TypePosition,
+) -> Vec<BrokenExport> {
+    let mut broken_exports = Vec::new();
+    let previous_type_param_count = previous_type.type_params.len();
+
+    for type_param in current_type
+        .type_params
+        .iter()
+        .skip(previous_type_param_count)
+    {
+        if !type_param.has_default {
+            broken_exports.push((
+                format!("Type parameter '{}' of {property_label}", type_param.name),
+                BreakType::AddedWithoutDefault,
+            ));
+        }
+    }
+
+    for type_param in previous_type
+        .type_params
+        .iter()
+        .skip(current_type.type_params.len())
+    {
+        broken_exports.push((
+            format!("Type parameter '{}' of {property_label}", type_param.name),
+            BreakType::Removed,
+        ));
+    }
+
+    for base_type in previous_type.extends.iter() {
+        if !current_type.extends.contains(base_type) {
+            broken_exports.push((
+                format!("Base type '{base_type}' of {property_label}"),
+                BreakType::Removed,
+            ));
+        }
+    }
+
+    let (TypeRepr::Object(previous_object), TypeRepr::Object(current_object)) =
+        (&previous_type.shape, &current_type.shape)
+    else {
+        broken_exports.extend(get_type_change(
+            export_label.into(),
+            &previous_type.shape,
+            &current_type.shape,
+            type_position,
+        ));
+
+        return broken_exports;
+    };
+
+    for (name, previous_property) in previous_object.properties.iter() {
+        let label = format!("Property '{name}' of {property_label}");
+
+        let Some(current_property) = current_object.properties.get(name) else {
+            broken_exports.push((label, BreakType::RemovedOrRenamed));
+            continue;
+        };
+
+        if type_position.is_input && previous_property.is_optional && !current_property.is_optional
+        {
+            broken_exports.push((label.to_owned(), BreakType::BecameRequired));
+        } else if type_position.is_output
+            && !previous_property.is_optional
+            && current_property.is_optional
+        {
+            broken_exports.push((label.to_owned(), BreakType::BecameOptional));
+        }
+
+        broken_exports.extend(get_type_change(
+            label,
+            &previous_property.type_repr,
+            &current_property.type_repr,
+            type_position,
+        ));
+    }
+
+    // Values consumers provide lack any property that didn't exist before.
+    if type_position.is_input {
+        for (name, current_property) in current_object.properties.iter() {
+            if !current_property.is_optional && !previous_object.properties.contains_key(name) {
+                broken_exports.push((
+                    format!("Property '{name}' of {property_label}"),
+                    BreakType::AddedAsRequired,
+                ));
+            }
+        }
+    }
+
+    for (key, previous_value) in previous_object.index_signatures.iter() {
+        let label = format!("Index signature [key: {key}] of {property_label}");
+
+        match current_object.index_signatures.get(key) {
+            Some(current_value) => broken_exports.extend(get_type_change(
+                label,
+                previous_value,
+                current_value,
+                type_position,
+            )),
+            None => broken_exports.push((label, BreakType::Removed)),
+        }
+    }
+
+    broken_exports
+}
+
+fn get_type_change(
+    label: String,
+    previous_type: &TypeRepr,
+    current_type: &TypeRepr,
+    type_position: TypePosition,
+) -> Option<BrokenExport> {
+    let from = previous_type.to_string();
+    let to = current_type.to_string();
+
+    let is_narrowed = !previous_type.is_assignable_to(current_type);
+    let is_widened = !current_type.is_assignable_to(previous_type);
+
+    // Unrelated types break consumers in any position.
+    match (is_narrowed, is_widened) {
+        (true, true) => Some((label, BreakType::TypeChanged { from, to })),
+        (true, false) if type_position.is_input => {
+            Some((label, BreakType::TypeNarrowed { from, to }))
+        }
+        (false, true) if type_position.is_output => {
+            Some((label, BreakType::TypeWidened { from, to }))
+        }
+        _ => None,
+    }
+}
+
+/// Compares the overload signatures of functions. A previous overload is still available
+/// when a current one accepts the same arguments and returns a compatible value.
+fn analyze_changes_between_overloads(
+    property_label: &str,
+    previous_signatures: &[FunctionType],
+    current_signatures: &[FunctionType],
+) -> Vec<BrokenExport> {
+    let mut broken_exports = Vec::new();
+
+    for previous_signature in previous_signatures {
+        let is_available = current_signatures
+            .iter()
+            .any(|current_signature| current_signature.is_assignable_to(previous_signature));
+
+        if is_available {
+            continue;
+        }
+
+        let label = format!("Overload '{previous_signature}' of {property_label}");
+
+        // An overload that still accepts the same arguments is the same one, with a changed
+        // return type. Otherwise, calls matching the previous overload no longer compile.
+        let changed_signature = current_signatures.iter().find(|current_signature| {
+            let params_only_signature = FunctionType {
+                params: current_signature.params.to_owned(),
+                returns: previous_signature.returns.to_owned(),
+            };
+
+            params_only_signature.is_assignable_to(previous_signature)
+        });
+
+        match changed_signature {
+            Some(current_signature) => broken_exports.push((
+                label,
+                BreakType::TypeNarrowed {
+                    from: previous_signature.to_string(),
+                    to: current_signature.to_string(),
+                },
+            )),
+            None => broken_exports.push((label, BreakType::Removed)),
+        }
+    }
+
+    broken_exports
+}
+
+/// Compares enum members by value, as consumers compiled against the previous version
+/// may have inlined them.
+fn analyze_changes_between_enum_members(
+    enum_label: &str,
+    previous_enum: &TsEnumDecl,
+    current_enum: &TsEnumDecl,
+) -> Vec<BrokenExport> {
+    let mut broken_exports = Vec::new();
+    let current_members: HashMap<String, Option<String>> =
+        get_enum_member_values(current_enum).into_iter().collect();
+
+    for (member_name, previous_value) in get_enum_member_values(previous_enum) {
+        let label = format!("Member '{member_name}' of {enum_label}");
+
+        match (previous_value, current_members.get(&member_name)) {
+            (_, None) => broken_exports.push((label, BreakType::RemovedOrRenamed)),
+            (Some(previous_value), Some(Some(current_value)))
+                if previous_value.ne(current_value) =>
+            {
+                broken_exports.push((
+                    label,
+                    BreakType::ValueChanged {
+                        from: previous_value,
+                        to: current_value.to_owned(),
+                        is_breaking: true,
+                    },
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    broken_exports
+}
+
+fn get_value_change(
+    label: String,
+    from: String,
+    to: String,
+    value_change_severity: ChangeSeverity,
+) -> Option<BrokenExport> {
+    if value_change_severity.eq(&ChangeSeverity::Ignored) {
+        return None;
+    }
+
+    let is_breaking = value_change_severity.eq(&ChangeSeverity::Breaking);
+
+    Some((
+        label,
+        BreakType::ValueChanged {
+            from,
+            to,
+            is_breaking,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecma::parser::parse_module_source;
+    use std::path::Path;
+    use swc_ecma_ast::{Decl, ModuleItem, Stmt};
+
+    fn get_type_declaration(source: &str) -> TypeDeclaration {
+        let (module, _) = parse_module_source(Path::new("index.ts"), source.into()).unwrap();
+
+        match module.body.into_iter().next() {
+            Some(ModuleItem::Stmt(Stmt::Decl(Decl::TsInterface(interface)))) => {
+                TypeDeclaration::from_interface(&interface)
+            }
+            Some(ModuleItem::Stmt(Stmt::Decl(Decl::TsTypeAlias(type_alias)))) => {
+                TypeDeclaration::from_type_alias(&type_alias)
+            }
+            _ => panic!("expected an interface or type alias"),
+        }
+    }
+
+    fn get_signatures(function_types: &[&str]) -> Vec<FunctionType> {
+        function_types
+            .iter()
+            .map(|function_type| {
+                match get_type_declaration(&format!("type T = {function_type};")).shape {
+                    TypeRepr::Function(function_type) => *function_type,
+                    _ => panic!("expected a function type"),
+                }
+            })
+            .collect()
+    }
+
+    fn get_overload_changes(previous: &[&str], current: &[&str]) -> Vec<String> {
+        analyze_changes_between_overloads(
+            "function 'f'",
+            &get_signatures(previous),
+            &get_signatures(current),
+        )
+        .into_iter()
+        .map(|(label, break_type)| format!("{label} {break_type}"))
+        .collect()
+    }
+
+    fn get_type_changes(previous: &str, current: &str, position: TypePosition) -> Vec<String> {
+        analyze_changes_between_types(
+            ("Type 'T'", "type 'T'"),
+            &get_type_declaration(previous),
+            &get_type_declaration(current),
+            position,
+        )
+        .into_iter()
+        .map(|(label, break_type)| format!("{label} {break_type}"))
+        .collect()
+    }
+
+    #[test]
+    fn overloads_still_accepting_previous_arguments_are_available() {
+        assert!(get_overload_changes(
+            &["(a: string) => void", "(a: number) => void"],
+            &["(a: string | number) => void"],
+        )
+        .is_empty());
+
+        assert!(get_overload_changes(
+            &["(a: string) => void", "(a: string, b: number) => void"],
+            &["(a: string, b?: number) => void"],
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn overloads_no_longer_accepting_previous_arguments_are_removed() {
+        assert_eq!(
+            get_overload_changes(
+                &["(a: string) => void", "(a: number) => void"],
+                &["(a: string) => void", "(a: boolean) => void"],
+            ),
+            ["Overload '(number) => void' of function 'f' removed"]
+        );
+
+        assert_eq!(
+            get_overload_changes(&["(a?: string) => void"], &["(a: string) => void"],),
+            ["Overload '(string?) => void' of function 'f' removed"]
+        );
+    }
+
+    #[test]
+    fn overloads_returning_other_values_are_narrowed() {
+        assert_eq!(
+            get_overload_changes(&["(a: string) => string"], &["(a: string) => number"]),
+            ["Overload '(string) => string' of function 'f' narrowed from (string) => string to (string) => number"]
+        );
+    }
+
+    #[test]
+    fn input_types_can_widen_but_not_narrow_or_gain_required_properties() {
+        assert_eq!(
+            get_type_changes(
+                "interface T { a: string | number; b?: string; c: string }",
+                "interface T { a: string; b: string; c: string | null; d: string; e?: string }",
+                TypePosition::INPUT,
+            ),
+            [
+                "Property 'a' of type 'T' narrowed from number | string to string",
+                "Property 'b' of type 'T' made required",
+                "Property 'd' of type 'T' added as required",
+            ]
+        );
+    }
+
+    #[test]
+    fn output_types_can_narrow_and_gain_properties_but_not_widen() {
+        assert_eq!(
+            get_type_changes(
+                "interface T { a: string | number; b: string; c: string }",
+                "interface T { a: string; b?: string; c: string | null; d: string }",
+                TypePosition::OUTPUT,
+            ),
+            [
+                "Property 'b' of type 'T' made optional",
+                "Property 'c' of type 'T' widened from string to null | string",
+            ]
+        );
+    }
+
+    #[test]
+    fn types_used_both_ways_can_neither_narrow_nor_widen() {
+        assert_eq!(
+            get_type_changes(
+                "type T = { a: string | number; b: string }",
+                "type T = { a: string; b: string | null; c: string }",
+                TypePosition::BOTH,
+            ),
+            [
+                "Property 'a' of type 'T' narrowed from number | string to string",
+                "Property 'b' of type 'T' widened from string to null | string",
+                "Property 'c' of type 'T' added as required",
+            ]
+        );
+    }
+
+    #[test]
+    fn unrelated_types_are_changed_in_every_position() {
+        for position in [
+            TypePosition::INPUT,
+            TypePosition::OUTPUT,
+            TypePosition::BOTH,
+        ] {
+            assert_eq!(
+                get_type_changes("type T = string", "type T = number", position),
+                ["Type 'T' changed from type string to number"]
+            );
+        }
+    }
+
+    #[test]
+    fn type_params_added_without_a_default_break_every_reference() {
+        assert_eq!(
+            get_type_changes(
+                "interface T<A> { a: A }",
+                "interface T<A, B = string, C> { a: A; b: B; c: C }",
+                TypePosition::OUTPUT,
+            ),
+            ["Type parameter 'C' of type 'T' added without a default"]
+        );
+    }
+
+    #[test]
+    fn removed_properties_and_index_signatures_break_every_position() {
+        for position in [TypePosition::INPUT, TypePosition::OUTPUT] {
+            assert_eq!(
+                get_type_changes(
+                    "interface T { a: string; b: string; [key: string]: string }",
+                    "interface T { a: string }",
+                    position,
+                ),
+                [
+                    "Property 'b' of type 'T' removed or renamed",
+                    "Index signature [key: string] of type 'T' removed",
+                ]
+            );
+        }
+    }
+
+    fn get_removal_breaks(doc_tags: Option<DocTags>) -> Vec<String> {
+        get_removed_export_breaks(
+            "Named export 'a'".into(),
+            BreakType::RemovedOrRenamed,
+            doc_tags.as_ref(),
+        )
+        .into_iter()
+        .map(|(label, break_type)| format!("{label} {break_type}"))
+        .collect()
+    }
+
+    #[test]
+    fn exports_removed_without_deprecation_are_reported_as_such() {
+        assert_eq!(
+            get_removal_breaks(None),
+            ["Named export 'a' removed or renamed without being deprecated first"]
+        );
+
+        let deprecated = DocTags {
+            is_deprecated: true,
+            ..DocTags::default()
+        };
+
+        assert_eq!(
+            get_removal_breaks(Some(deprecated)),
+            ["Named export 'a' removed or renamed"]
+        );
+    }
+
+    #[test]
+    fn changes_to_internal_exports_are_left_out() {
+        let internal = DocTags {
+            release_tag: Some(ReleaseTag::Internal),
+            is_deprecated: false,
+        };
+
+        assert!(get_removal_breaks(Some(internal)).is_empty());
+        assert!(apply_release_tag(
+            vec![("Named export 'a'".into(), BreakType::BecameRequired)],
+            Some(&internal),
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn changes_to_pre_release_exports_are_potential() {
+        for (release_tag, tag_name) in [(ReleaseTag::Beta, "@beta"), (ReleaseTag::Alpha, "@alpha")]
+        {
+            let doc_tags = DocTags {
+                release_tag: Some(release_tag),
+                is_deprecated: true,
+            };
+
+            let broken_exports = apply_release_tag(
+                vec![("Named export 'a'".into(), BreakType::Removed)],
+                Some(&doc_tags),
+            );
+
+            assert!(broken_exports[0].1.is_potential());
+            assert_eq!(
+                get_removal_breaks(Some(doc_tags)),
+                [format!(
+                    "Named export 'a' removed or renamed, but is tagged {tag_name}"
+                )]
+            );
+        }
+    }
+}
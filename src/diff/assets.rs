@@ -1,23 +1,119 @@
+use crate::diff::jobs::run_jobs;
+use crate::diff::options::DiffOptions;
+use crate::diff::results::{
+    BreakType, BrokenExport, BrokenTypesResult, ModifiedAsset, MovedAsset, TypesBreakType,
+};
 use crate::ecma::entity::EntityDeclaration;
-use crate::ecma::parser::parse_pkg_entry;
-use crate::ecma::walker::get_exports_in_module;
+use crate::ecma::graph::{
+    get_package_name, ExternalExport, ImportName, ModuleExports, ModuleGraph,
+};
+use crate::ecma::positions::{get_type_positions, TypePosition};
+use crate::fs::path::path_matches_a_pattern_in;
 use crate::pkg::contents::PkgContents;
 use crate::pkg::entries::PkgEntry;
+use crate::pkg::types::{PkgTypesEntry, TsResolutionMode, TsVersion, TsVersionRange};
+use crate::pkg::Pkg;
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use hmac_sha512::Hash;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::Arc;
+use strum::IntoEnumIterator;
 
 type PkgEntriesMissing<'entry> = Vec<&'entry String>;
 type PkgEntriesMatching<'entry> = HashMap<&'entry String, (&'entry PkgEntry, &'entry PkgEntry)>;
+type PkgEntriesMoved<'entry> = HashMap<&'entry String, &'entry PkgEntry>;
 
-type PkgEntryDefaultExport = Option<EntityDeclaration>;
+type PkgEntryDefaultExport = Option<(EntityDeclaration, EntityDeclaration)>;
 type PkgEntryNamedExportsMissing = Vec<String>;
-type PkgEntryNamedExportsMatching = HashMap<String, EntityDeclaration>;
+type PkgEntryNamedExportsMatching = HashMap<String, (EntityDeclaration, EntityDeclaration)>;
+type PkgEntryNamedExportsAmbiguous = HashMap<String, (String, String)>;
+type PkgEntryExportsPotentiallyBroken = Vec<BrokenExport>;
+type PkgEntryTypePositions = HashMap<String, TypePosition>;
+
+/// Dependencies whose version range changed major version, with the previous & current
+/// ranges.
+pub type PkgDependencyChanges = BTreeMap<String, (String, String)>;
+
+struct MoveCandidate<'item, Key> {
+    key: Key,
+    path: &'item PathBuf,
+    hash: Option<Vec<u8>>,
+}
+
+impl<'item, Key> MoveCandidate<'item, Key> {
+    fn new(key: Key, path: &'item PathBuf, hash: Option<Vec<u8>>) -> Self {
+        Self { key, path, hash }
+    }
+}
 
 pub fn diff_pkg_assets(
     previous_contents: &PkgContents,
     current_contents: &PkgContents,
-) -> Result<Vec<PathBuf>> {
+) -> Result<(Vec<PathBuf>, Vec<MovedAsset>)> {
+    let previous_assets = previous_contents
+        .asset_list()
+        .with_context(|| "Failed to get list of previous package's assets.")?;
+
+    let current_assets = current_contents
+        .asset_list()
+        .with_context(|| "Failed to get list of current package's assets.")?;
+
+    let mut removed_assets: Vec<&PathBuf> = previous_assets.difference(&current_assets).collect();
+
+    let mut added_assets: Vec<&PathBuf> = current_assets.difference(&previous_assets).collect();
+
+    removed_assets.sort();
+    added_assets.sort();
+
+    let mut removed_candidates = Vec::with_capacity(removed_assets.len());
+    let mut added_candidates = Vec::with_capacity(added_assets.len());
+
+    for asset_path in removed_assets {
+        let asset_hash = get_asset_hash_and_size(previous_contents, asset_path)
+            .with_context(|| format!("Failed to hash previous asset: {}", asset_path.display()))?
+            .map(|(hash, _)| hash);
+
+        removed_candidates.push(MoveCandidate::new(asset_path, asset_path, asset_hash));
+    }
+
+    for asset_path in added_assets {
+        let asset_hash = get_asset_hash_and_size(current_contents, asset_path)
+            .with_context(|| format!("Failed to hash current asset: {}", asset_path.display()))?
+            .map(|(hash, _)| hash);
+
+        added_candidates.push(MoveCandidate::new(asset_path, asset_path, asset_hash));
+    }
+
+    let moved_pairs = pair_moved_candidates(&removed_candidates, &added_candidates);
+    let mut moved_assets = Vec::with_capacity(moved_pairs.len());
+
+    let removed_assets = removed_candidates
+        .iter()
+        .filter(|candidate| {
+            !moved_pairs
+                .iter()
+                .any(|(removed_path, _, _)| removed_path.eq(&candidate.key))
+        })
+        .map(|candidate| candidate.key.to_owned())
+        .collect();
+
+    for (previous_path, current_path, is_identical) in moved_pairs {
+        moved_assets.push(MovedAsset {
+            previous_path: previous_path.to_owned(),
+            current_path: current_path.to_owned(),
+            is_identical,
+        });
+    }
+
+    Ok((removed_assets, moved_assets))
+}
+
+pub fn diff_pkg_asset_contents(
+    previous_contents: &PkgContents,
+    current_contents: &PkgContents,
+    options: &DiffOptions,
+) -> Result<Vec<ModifiedAsset>> {
     let previous_assets = previous_contents
         .asset_list()
         .with_context(|| "Failed to get list of previous package's assets.")?;
@@ -26,18 +122,69 @@ pub fn diff_pkg_assets(
         .asset_list()
         .with_context(|| "Failed to get list of current package's assets.")?;
 
-    Ok(previous_assets
-        .difference(&current_assets)
-        .map(|asset_path| asset_path.to_owned())
-        .collect())
+    // The package.json changes with every release, and its relevant fields are analyzed
+    // through the package entries instead.
+    let mut matching_assets: Vec<&PathBuf> = previous_assets
+        .intersection(&current_assets)
+        .filter(|asset_path| asset_path.ne(&&PathBuf::from("package.json")))
+        .collect();
+
+    matching_assets.sort();
+
+    let modified_assets = run_jobs(&matching_assets, options.jobs, |asset_path| {
+        let previous_asset = get_asset_hash_and_size(previous_contents, asset_path)
+            .with_context(|| format!("Failed to hash previous asset: {}", asset_path.display()))?;
+
+        let current_asset = get_asset_hash_and_size(current_contents, asset_path)
+            .with_context(|| format!("Failed to hash current asset: {}", asset_path.display()))?;
+
+        let ((previous_hash, previous_size), (current_hash, current_size)) =
+            match (previous_asset, current_asset) {
+                (Some(previous_asset), Some(current_asset)) => (previous_asset, current_asset),
+                _ => return Ok(None),
+            };
+
+        if previous_hash.eq(&current_hash) {
+            return Ok(None);
+        }
+
+        // Informational patterns take precedence, so that specific files can be carved
+        // out of broader breaking globs.
+        let is_breaking =
+            !path_matches_a_pattern_in(asset_path, &options.informational_asset_patterns)
+                && path_matches_a_pattern_in(asset_path, &options.breaking_asset_patterns);
+
+        Ok(Some(ModifiedAsset {
+            path: asset_path.to_path_buf(),
+            previous_size,
+            current_size,
+            is_breaking,
+        }))
+    })?;
+
+    Ok(modified_assets.into_iter().flatten().collect())
+}
+
+fn get_asset_hash_and_size(
+    contents: &PkgContents,
+    asset_path: &PathBuf,
+) -> Result<Option<(Vec<u8>, usize)>> {
+    Ok(contents
+        .load_file(asset_path)?
+        .map(|data| (Hash::hash(&data).to_vec(), data.len())))
 }
 
 pub fn diff_pkg_entries<'entry>(
     previous_entries: &'entry HashMap<String, PkgEntry>,
     current_entries: &'entry HashMap<String, PkgEntry>,
-) -> Result<(PkgEntriesMissing<'entry>, PkgEntriesMatching<'entry>)> {
+) -> Result<(
+    PkgEntriesMissing<'entry>,
+    PkgEntriesMatching<'entry>,
+    PkgEntriesMoved<'entry>,
+)> {
     let mut missing_entries = PkgEntriesMissing::new();
     let mut matching_entries = PkgEntriesMatching::new();
+    let mut moved_entries = PkgEntriesMoved::new();
 
     for (previous_entry_name, previous_entry) in previous_entries.iter() {
         if let Some(matching_current_entry) = current_entries.get(previous_entry_name) {
@@ -50,69 +197,231 @@ pub fn diff_pkg_entries<'entry>(
         }
     }
 
-    Ok((missing_entries, matching_entries))
+    if missing_entries.is_empty() {
+        return Ok((missing_entries, matching_entries, moved_entries));
+    }
+
+    missing_entries.sort();
+
+    let mut added_entries: Vec<&String> = current_entries
+        .keys()
+        .filter(|entry_name| !previous_entries.contains_key(*entry_name))
+        .collect();
+
+    added_entries.sort();
+
+    let removed_candidates = get_entry_move_candidates(&missing_entries, previous_entries)
+        .with_context(|| "Failed to gather previous entries for move detection.")?;
+
+    let added_candidates = get_entry_move_candidates(&added_entries, current_entries)
+        .with_context(|| "Failed to gather current entries for move detection.")?;
+
+    for (previous_entry_name, current_entry_name, _) in
+        pair_moved_candidates(&removed_candidates, &added_candidates)
+    {
+        moved_entries.insert(previous_entry_name, &current_entries[current_entry_name]);
+    }
+
+    Ok((missing_entries, matching_entries, moved_entries))
+}
+
+fn get_entry_move_candidates<'entry>(
+    entry_names: &[&'entry String],
+    entries: &'entry HashMap<String, PkgEntry>,
+) -> Result<Vec<MoveCandidate<'entry, &'entry String>>> {
+    let mut candidates = Vec::with_capacity(entry_names.len());
+
+    for entry_name in entry_names {
+        let entry = &entries[*entry_name];
+        let entry_hash = entry.load_file()?.map(|data| Hash::hash(&data).to_vec());
+
+        candidates.push(MoveCandidate::new(*entry_name, &entry.path, entry_hash));
+    }
+
+    Ok(candidates)
+}
+
+/// Pairs removed & added items that are likely the same item moved elsewhere. Items
+/// with identical contents are paired first, and remaining items are paired when they
+/// share a file name with exactly one other item. Returns the paired keys, and whether
+/// the pair has identical contents.
+fn pair_moved_candidates<'item, Key: Copy + Eq + std::hash::Hash>(
+    removed_candidates: &[MoveCandidate<'item, Key>],
+    added_candidates: &[MoveCandidate<'item, Key>],
+) -> Vec<(Key, Key, bool)> {
+    let mut pairs: Vec<(Key, Key, bool)> = Vec::new();
+    let mut paired_removed_keys: HashSet<Key> = HashSet::new();
+    let mut paired_added_keys: HashSet<Key> = HashSet::new();
+
+    for removed in removed_candidates {
+        let identical_candidate = added_candidates.iter().find(|added| {
+            removed.hash.is_some()
+                && removed.hash.eq(&added.hash)
+                && !paired_added_keys.contains(&added.key)
+        });
+
+        if let Some(added) = identical_candidate {
+            pairs.push((removed.key, added.key, true));
+            paired_removed_keys.insert(removed.key);
+            paired_added_keys.insert(added.key);
+        }
+    }
+
+    for removed in removed_candidates {
+        if paired_removed_keys.contains(&removed.key) || removed.path.file_name().is_none() {
+            continue;
+        }
+
+        let similar_candidates: Vec<&MoveCandidate<Key>> = added_candidates
+            .iter()
+            .filter(|added| {
+                added.path.file_name().eq(&removed.path.file_name())
+                    && !paired_added_keys.contains(&added.key)
+            })
+            .collect();
+
+        if let [added] = similar_candidates.as_slice() {
+            pairs.push((removed.key, added.key, false));
+            paired_removed_keys.insert(removed.key);
+            paired_added_keys.insert(added.key);
+        }
+    }
+
+    pairs
 }
 
 pub fn diff_pkg_entry_exports(
     previous_entry: &PkgEntry,
     current_entry: &PkgEntry,
+    dependency_changes: &PkgDependencyChanges,
+    resolve_node_modules: bool,
 ) -> Result<(
     bool,
     PkgEntryDefaultExport,
     PkgEntryNamedExportsMissing,
+    PkgEntryNamedExportsAmbiguous,
     PkgEntryNamedExportsMatching,
+    PkgEntryExportsPotentiallyBroken,
+    PkgEntryTypePositions,
 )> {
     let mut is_default_export_missing = false;
     let mut matching_default_export = None;
 
     let mut missing_named_exports = PkgEntryNamedExportsMissing::new();
+    let mut ambiguous_named_exports = PkgEntryNamedExportsAmbiguous::new();
     let mut matching_named_exports = PkgEntryNamedExportsMatching::new();
+    let mut potentially_broken_exports = PkgEntryExportsPotentiallyBroken::new();
 
-    let previous_module = parse_pkg_entry(previous_entry).with_context(|| {
+    // Only the local package has its dependencies installed, so re-exports of the previous
+    // version are never resolved.
+    let previous_exports = get_pkg_entry_exports(previous_entry, false).with_context(|| {
         format!(
-            "Failed to parse previous package entry module: {}",
+            "Failed to get exports from previous package entry module: {}",
             previous_entry.name
         )
     })?;
 
-    let current_module = parse_pkg_entry(current_entry).with_context(|| {
-        format!(
-            "Failed to parse current package entry module: {}",
-            current_entry.name
-        )
-    })?;
-
-    let (previous_default_export, previous_named_exports) =
-        get_exports_in_module(previous_entry.dir_path(), previous_module).with_context(|| {
-            format!(
-                "Failed to get exports from previous package entry module: {}",
-                previous_entry.name
-            )
-        })?;
-
-    let (current_default_export, mut current_named_exports) =
-        get_exports_in_module(current_entry.dir_path(), current_module).with_context(|| {
+    let current_exports =
+        get_pkg_entry_exports(current_entry, resolve_node_modules).with_context(|| {
             format!(
                 "Failed to get exports from current package entry module: {}",
                 current_entry.name
             )
         })?;
 
-    if previous_default_export.is_some() {
-        if current_default_export.is_some() {
-            matching_default_export = current_default_export;
+    let previous_external_default_export = previous_exports.external_exports.get("default");
+
+    if previous_exports.default_export.is_some() || previous_external_default_export.is_some() {
+        if let Some(current_default_export) = &current_exports.default_export {
+            // Previously re-exported defaults have no declaration to compare against.
+            matching_default_export =
+                previous_exports
+                    .default_export
+                    .as_ref()
+                    .map(|previous_default_export| {
+                        (
+                            previous_default_export.to_owned(),
+                            current_default_export.to_owned(),
+                        )
+                    });
+        } else if let Some(current_external_export) =
+            current_exports.external_exports.get("default")
+        {
+            add_external_export_potential_breaks(
+                String::from("Default export"),
+                previous_external_default_export,
+                current_external_export,
+                dependency_changes,
+                &mut potentially_broken_exports,
+            );
         } else {
             is_default_export_missing = true;
         }
     }
 
-    for (previous_export_name, _) in previous_named_exports {
-        let matching_named_export = current_named_exports.remove(&previous_export_name);
+    let previous_export_names = previous_exports
+        .named_exports
+        .keys()
+        .chain(previous_exports.external_exports.keys())
+        .filter(|name| name.ne(&"default"));
+
+    for previous_export_name in previous_export_names {
+        let matching_named_export = current_exports.named_exports.get(previous_export_name);
 
         if let Some(matching_named_export) = matching_named_export {
-            matching_named_exports.insert(previous_export_name, matching_named_export);
+            if let Some(previous_named_export) =
+                previous_exports.named_exports.get(previous_export_name)
+            {
+                matching_named_exports.insert(
+                    previous_export_name.to_owned(),
+                    (
+                        previous_named_export.to_owned(),
+                        matching_named_export.to_owned(),
+                    ),
+                );
+            }
+        } else if let Some(current_external_export) =
+            current_exports.external_exports.get(previous_export_name)
+        {
+            add_external_export_potential_breaks(
+                format!("Named export '{previous_export_name}'"),
+                previous_exports.external_exports.get(previous_export_name),
+                current_external_export,
+                dependency_changes,
+                &mut potentially_broken_exports,
+            );
+        } else if let Some(origin_paths) =
+            current_exports.ambiguous_exports.get(previous_export_name)
+        {
+            ambiguous_named_exports
+                .insert(previous_export_name.to_owned(), origin_paths.to_owned());
+        } else if !current_exports.external_star_exports.is_empty() {
+            // Other packages re-exported with `export *` may still provide it.
+            potentially_broken_exports.push((
+                format!("Named export '{previous_export_name}'"),
+                BreakType::PossiblyRemoved {
+                    specifiers: format_specifiers(&current_exports.external_star_exports),
+                },
+            ));
         } else {
-            missing_named_exports.push(previous_export_name);
+            missing_named_exports.push(previous_export_name.to_owned());
+        }
+    }
+
+    let kept_external_star_exports = previous_exports
+        .external_star_exports
+        .intersection(&current_exports.external_star_exports);
+
+    for specifier in kept_external_star_exports {
+        if let Some((from, to)) = dependency_changes.get(get_package_name(specifier)) {
+            potentially_broken_exports.push((
+                format!("Star re-export from '{specifier}'"),
+                BreakType::DependencyRangeChanged {
+                    package_name: get_package_name(specifier).into(),
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                },
+            ));
         }
     }
 
@@ -120,6 +429,306 @@ pub fn diff_pkg_entry_exports(
         is_default_export_missing,
         matching_default_export,
         missing_named_exports,
+        ambiguous_named_exports,
         matching_named_exports,
+        potentially_broken_exports,
+        get_type_positions_in_either(&previous_exports, &current_exports),
     ))
 }
+
+/// The UMD global of the previous entry when the current entry no longer declares it. Scripts
+/// use the global by name, so renaming it breaks them as well.
+pub fn diff_pkg_entry_global_names(
+    previous_entry: &PkgEntry,
+    current_entry: &PkgEntry,
+    resolve_node_modules: bool,
+) -> Result<Option<String>> {
+    let previous_exports = get_pkg_entry_exports(previous_entry, false)?;
+    let current_exports = get_pkg_entry_exports(current_entry, resolve_node_modules)?;
+
+    Ok(previous_exports
+        .global_name
+        .to_owned()
+        .filter(|global_name| current_exports.global_name.as_ref().ne(&Some(global_name))))
+}
+
+/// The positions types are used in by the exports of either version, as consumers may rely
+/// on any of them.
+fn get_type_positions_in_either(
+    previous_exports: &ModuleExports,
+    current_exports: &ModuleExports,
+) -> PkgEntryTypePositions {
+    let get_exports_type_positions = |exports: &ModuleExports| {
+        get_type_positions(
+            exports
+                .default_export
+                .iter()
+                .chain(exports.named_exports.values()),
+        )
+    };
+
+    let mut type_positions = get_exports_type_positions(previous_exports);
+
+    for (name, position) in get_exports_type_positions(current_exports) {
+        let known_position = type_positions.entry(name).or_default();
+        *known_position = known_position.union(position);
+    }
+
+    type_positions
+}
+
+/// Adds the potential breaks of an export that is re-exported from another package in
+/// the current version, whose names are not known unless it is resolved.
+fn add_external_export_potential_breaks(
+    export_label: String,
+    previous_external_export: Option<&ExternalExport>,
+    current_external_export: &ExternalExport,
+    dependency_changes: &PkgDependencyChanges,
+    buffer: &mut PkgEntryExportsPotentiallyBroken,
+) {
+    if let Some(previous_external_export) = previous_external_export {
+        if previous_external_export.ne(current_external_export) {
+            buffer.push((
+                export_label,
+                BreakType::ExternalSourceChanged {
+                    from: format_external_export(previous_external_export),
+                    to: format_external_export(current_external_export),
+                },
+            ));
+
+            return;
+        }
+    }
+
+    if let Some((from, to)) = dependency_changes.get(&current_external_export.package_name) {
+        buffer.push((
+            export_label,
+            BreakType::DependencyRangeChanged {
+                package_name: current_external_export.package_name.to_owned(),
+                from: from.to_owned(),
+                to: to.to_owned(),
+            },
+        ));
+    }
+}
+
+fn format_external_export(external_export: &ExternalExport) -> String {
+    match &external_export.import_name {
+        ImportName::Named(name) => format!("{name} from '{}'", external_export.specifier),
+        ImportName::Namespace => format!("the namespace of '{}'", external_export.specifier),
+    }
+}
+
+fn format_specifiers(specifiers: &BTreeSet<String>) -> String {
+    specifiers
+        .iter()
+        .map(|specifier| format!("'{specifier}'"))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+pub fn get_pkg_entry_exports(
+    entry: &PkgEntry,
+    resolve_node_modules: bool,
+) -> Result<Arc<ModuleExports>> {
+    ModuleGraph::new(&entry.contents, resolve_node_modules)
+        .get_exports_of(&entry.path)
+        .with_context(|| {
+            format!(
+                "Failed to get exports of package entry module: {}",
+                entry.name
+            )
+        })
+}
+
+/// Finds the runtime dependencies whose version range now allows a different major
+/// version, i.e. whose exports may have changed in breaking ways.
+pub fn diff_pkg_dependencies(previous_pkg: &Pkg, current_pkg: &Pkg) -> PkgDependencyChanges {
+    let mut dependency_changes = PkgDependencyChanges::new();
+
+    for (dependency_name, current_range) in current_pkg.dependencies.iter() {
+        let previous_range = match previous_pkg.dependencies.get(dependency_name) {
+            Some(previous_range) => previous_range,
+            None => continue,
+        };
+
+        let previous_major = get_range_major(previous_range);
+        let current_major = get_range_major(current_range);
+
+        if previous_major.is_some() && current_major.is_some() && previous_major.ne(&current_major)
+        {
+            dependency_changes.insert(
+                dependency_name.to_owned(),
+                (previous_range.to_owned(), current_range.to_owned()),
+            );
+        }
+    }
+
+    dependency_changes
+}
+
+/// Returns the major version of the lowest version a range allows, along with its minor
+/// version for 0.x versions, whose minor releases are breaking.
+fn get_range_major(range: &str) -> Option<(u64, u64)> {
+    let version = range.trim_start_matches(|char: char| !char.is_ascii_digit());
+    let mut numbers = version
+        .split(|char: char| !char.is_ascii_digit())
+        .map(|number| number.parse::<u64>());
+
+    let major = numbers.next()?.ok()?;
+
+    if major.gt(&0) {
+        return Some((major, 0));
+    }
+
+    let minor = numbers.next().and_then(|minor| minor.ok()).unwrap_or(0);
+
+    Some((major, minor))
+}
+
+pub fn diff_pkg_types(
+    previous_types: &BTreeMap<String, PkgTypesEntry>,
+    current_types: &BTreeMap<String, PkgTypesEntry>,
+    ts_versions: &TsVersionRange,
+) -> Vec<BrokenTypesResult> {
+    let mut broken_types = Vec::new();
+
+    for (subpath, previous_entry) in previous_types {
+        let current_entry = current_types.get(subpath);
+
+        let previous_resolutions = previous_entry
+            .resolutions
+            .iter()
+            .filter(|((_, version), _)| ts_versions.matches(version));
+
+        let mut unresolved_versions: BTreeMap<TsResolutionMode, Vec<TsVersion>> = BTreeMap::new();
+        let mut uncovered_versions: BTreeSet<TsVersion> = BTreeSet::new();
+        let mut had_types = false;
+        let mut has_types = false;
+
+        for (key, previous_resolution) in previous_resolutions {
+            let (mode, version) = key;
+            let current_resolution =
+                current_entry.and_then(|current_entry| current_entry.resolutions.get(key));
+
+            let current_path = current_resolution.and_then(|resolution| resolution.path.as_ref());
+            let current_range =
+                current_resolution.and_then(|resolution| resolution.types_versions_range.as_ref());
+
+            had_types |= previous_resolution.path.is_some();
+            has_types |= current_path.is_some();
+
+            if previous_resolution.path.is_some() && current_path.is_none() {
+                unresolved_versions.entry(*mode).or_default().push(*version);
+            }
+
+            if previous_resolution.types_versions_range.is_some() && current_range.is_none() {
+                uncovered_versions.insert(*version);
+            }
+        }
+
+        if !had_types {
+            continue;
+        }
+
+        let mut breaks = Vec::new();
+
+        if !has_types {
+            breaks.push(TypesBreakType::Removed);
+        } else {
+            for mode in TsResolutionMode::iter() {
+                if let Some(versions) = unresolved_versions.get(&mode) {
+                    breaks.push(TypesBreakType::Unresolved {
+                        mode,
+                        versions: format_ts_versions(versions),
+                    });
+                }
+            }
+        }
+
+        if !uncovered_versions.is_empty() {
+            let uncovered_versions: Vec<TsVersion> = uncovered_versions.into_iter().collect();
+
+            breaks.push(TypesBreakType::TypesVersionsUncovered {
+                versions: format_ts_versions(&uncovered_versions),
+            });
+        }
+
+        if !breaks.is_empty() {
+            broken_types.push(BrokenTypesResult {
+                subpath: subpath.to_owned(),
+                breaks,
+            });
+        }
+    }
+
+    broken_types
+}
+
+/// Formats sorted TypeScript versions as compact ranges of consecutive releases,
+/// e.g. "4.7-4.9, 5.4".
+fn format_ts_versions(versions: &[TsVersion]) -> String {
+    let releases = TsVersion::releases();
+    let release_index =
+        |version: &TsVersion| releases.iter().position(|release| release.eq(version));
+
+    let mut ranges: Vec<(TsVersion, TsVersion)> = Vec::new();
+
+    for version in versions {
+        if let Some((_, range_end)) = ranges.last_mut() {
+            let is_consecutive = match (release_index(range_end), release_index(version)) {
+                (Some(end_index), Some(index)) => index.eq(&(end_index + 1)),
+                _ => false,
+            };
+
+            if is_consecutive {
+                *range_end = *version;
+                continue;
+            }
+        }
+
+        ranges.push((*version, *version));
+    }
+
+    ranges
+        .iter()
+        .map(|(range_start, range_end)| {
+            if range_start.eq(range_end) {
+                range_start.to_string()
+            } else {
+                format!("{range_start}-{range_end}")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(versions: &[(u32, u32)]) -> String {
+        let versions: Vec<TsVersion> = versions
+            .iter()
+            .map(|(major, minor)| TsVersion::new(*major, *minor))
+            .collect();
+
+        format_ts_versions(&versions)
+    }
+
+    #[test]
+    fn consecutive_releases_are_formatted_as_ranges() {
+        assert_eq!(format(&[(4, 7), (4, 8), (4, 9), (5, 4)]), "4.7-4.9, 5.4");
+        assert_eq!(
+            format(&[(4, 9), (5, 0), (5, 1), (5, 3), (6, 0)]),
+            "4.9-5.1, 5.3, 6.0"
+        );
+    }
+
+    #[test]
+    fn single_and_unknown_versions_are_listed_alone() {
+        assert_eq!(format(&[]), "");
+        assert_eq!(format(&[(5, 0)]), "5.0");
+        assert_eq!(format(&[(4, 2), (4, 3)]), "4.2, 4.3");
+    }
+}
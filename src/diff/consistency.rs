@@ -0,0 +1,149 @@
+use crate::diff::assets::get_pkg_entry_exports;
+use crate::diff::jobs::run_jobs;
+use crate::diff::results::{ConsistencyResults, InconsistentExport, InconsistentSubpathResult};
+use crate::pkg::entries::{PkgEntry, PkgEntryType};
+use crate::pkg::Pkg;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+type SubpathEntries<'pkg> = BTreeMap<&'pkg String, Vec<(String, &'pkg PkgEntry)>>;
+
+/// Compares the exports of all entries that resolve to the same public subpath within
+/// a single package version, e.g. the `import` and `require` builds of a dual package.
+pub fn get_consistency_of(pkg: &Pkg, jobs: usize) -> Result<ConsistencyResults> {
+    let mut results = ConsistencyResults::default();
+    let mut subpath_entries = SubpathEntries::new();
+
+    add_subpath_entries(&mut subpath_entries, PkgEntryType::Main, &pkg.entries.main);
+    add_subpath_entries(
+        &mut subpath_entries,
+        PkgEntryType::Module,
+        &pkg.entries.module,
+    );
+    add_subpath_entries(
+        &mut subpath_entries,
+        PkgEntryType::JsNextMain,
+        &pkg.entries.jsnext_main,
+    );
+    add_subpath_entries(
+        &mut subpath_entries,
+        PkgEntryType::Browser,
+        &pkg.entries.browser,
+    );
+    add_subpath_entries(
+        &mut subpath_entries,
+        PkgEntryType::Exports,
+        &pkg.entries.exports,
+    );
+
+    let subpath_entries: Vec<_> = subpath_entries
+        .into_iter()
+        .filter(|(_, entries)| entries.len().ge(&2))
+        .collect();
+
+    let subpath_results = run_jobs(&subpath_entries, jobs, |(subpath, entries)| {
+        get_inconsistent_exports_between(entries)
+            .with_context(|| format!("Failed to compare entries of subpath '{subpath}'."))
+    })?;
+
+    for ((subpath, _), inconsistent_exports) in subpath_entries.iter().zip(subpath_results) {
+        if !inconsistent_exports.is_empty() {
+            results
+                .inconsistent_subpaths
+                .push(InconsistentSubpathResult {
+                    subpath: subpath.to_string(),
+                    inconsistent_exports,
+                });
+        }
+    }
+
+    results.export_cycles = pkg.contents.module_cache().get_cycles();
+
+    Ok(results)
+}
+
+fn add_subpath_entries<'pkg>(
+    buffer: &mut SubpathEntries<'pkg>,
+    entry_type: PkgEntryType,
+    entries: &'pkg HashMap<String, PkgEntry>,
+) {
+    for entry in entries.values() {
+        if let Some(subpath) = &entry.subpath {
+            let label = if entry_type.is_single_file_field() {
+                format!("{} entry at {}", entry_type, entry.path.display())
+            } else {
+                format!(
+                    "{} entry {} at {}",
+                    entry_type,
+                    entry.name,
+                    entry.path.display()
+                )
+            };
+
+            buffer.entry(subpath).or_default().push((label, entry));
+        }
+    }
+}
+
+fn get_inconsistent_exports_between(
+    entries: &[(String, &PkgEntry)],
+) -> Result<Vec<InconsistentExport>> {
+    let mut export_names_by_path: HashMap<&PathBuf, BTreeSet<String>> = HashMap::new();
+    let mut all_export_names: BTreeSet<String> = BTreeSet::new();
+
+    for (_, entry) in entries {
+        // Several entries commonly point to the same file; it only needs to be analyzed once.
+        if export_names_by_path.contains_key(&entry.path) {
+            continue;
+        }
+
+        let entry_exports = get_pkg_entry_exports(entry, false)
+            .with_context(|| format!("Failed to get exports of entry: {}", entry.name))?;
+
+        let mut export_names: BTreeSet<String> = entry_exports
+            .named_exports
+            .keys()
+            .chain(entry_exports.external_exports.keys())
+            .filter(|name| name.ne(&"default"))
+            .map(|name| format!("Named export '{name}'"))
+            .collect();
+
+        if entry_exports.default_export.is_some()
+            || entry_exports.external_exports.contains_key("default")
+        {
+            export_names.insert(String::from("Default export"));
+        }
+
+        all_export_names.extend(export_names.iter().cloned());
+        export_names_by_path.insert(&entry.path, export_names);
+    }
+
+    let mut inconsistent_exports = Vec::new();
+
+    for export_name in all_export_names {
+        let mut present_in = Vec::new();
+        let mut missing_from = Vec::new();
+
+        for (label, entry) in entries {
+            if export_names_by_path[&entry.path].contains(&export_name) {
+                present_in.push(label.to_owned());
+            } else {
+                missing_from.push(label.to_owned());
+            }
+        }
+
+        if !missing_from.is_empty() {
+            present_in.sort();
+            missing_from.sort();
+
+            inconsistent_exports.push(InconsistentExport {
+                export_name,
+                present_in,
+                missing_from,
+            });
+        }
+    }
+
+    Ok(inconsistent_exports)
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Runs a job for each input on a pool of scoped threads, and returns the outputs in the
+/// order of the inputs regardless of which job finishes first. A job count of 0 uses
+/// the available parallelism.
+pub fn run_jobs<Input, Output, Job>(
+    inputs: &[Input],
+    job_count: usize,
+    job: Job,
+) -> Result<Vec<Output>>
+where
+    Input: Sync,
+    Output: Send,
+    Job: Fn(&Input) -> Result<Output> + Sync,
+{
+    let thread_count = get_thread_count(job_count).min(inputs.len());
+
+    if thread_count.le(&1) {
+        return inputs.iter().map(job).collect();
+    }
+
+    let next_input_index = AtomicUsize::new(0);
+    let outputs: Mutex<Vec<Option<Result<Output>>>> =
+        Mutex::new(inputs.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..thread_count {
+            scope.spawn(|| loop {
+                let input_index = next_input_index.fetch_add(1, Ordering::Relaxed);
+
+                let input = match inputs.get(input_index) {
+                    Some(input) => input,
+                    None => break,
+                };
+
+                let output = job(input);
+
+                outputs.lock().unwrap()[input_index] = Some(output);
+            });
+        }
+    });
+
+    outputs
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn get_thread_count(job_count: usize) -> usize {
+    if job_count.gt(&0) {
+        return job_count;
+    }
+
+    thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+}
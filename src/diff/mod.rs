@@ -1,4 +1,7 @@
 pub mod analyzer;
 mod assets;
+pub mod consistency;
+mod jobs;
+pub mod options;
 pub mod printer;
 mod results;
@@ -0,0 +1,143 @@
+use crate::pkg::tarball::TarballLimits;
+use crate::pkg::types::TsVersionRange;
+use anyhow::{bail, Context, Result};
+use glob::Pattern;
+use json::JsonValue;
+
+/// How changes that are not always breaking are reported.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum ChangeSeverity {
+    Breaking,
+    #[default]
+    Warning,
+    Ignored,
+}
+
+impl ChangeSeverity {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "breaking" => Ok(ChangeSeverity::Breaking),
+            "warning" => Ok(ChangeSeverity::Warning),
+            "ignore" => Ok(ChangeSeverity::Ignored),
+            _ => bail!("Expected a severity of 'breaking', 'warning' or 'ignore', got: {value}"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DiffOptions {
+    /// TypeScript versions to check the types resolution of each subpath against.
+    pub ts_versions: TsVersionRange,
+    /// Whether assets present in both versions are compared by content.
+    pub compare_asset_contents: bool,
+    /// Assets that are breaking changes when modified.
+    pub breaking_asset_patterns: Vec<Pattern>,
+    /// Assets that are reported for information only when modified. Modified assets
+    /// matching neither set of patterns are informational as well.
+    pub informational_asset_patterns: Vec<Pattern>,
+    /// Bounds on the previous version's tarball.
+    pub tarball_limits: TarballLimits,
+    /// Number of threads entries are analyzed on, or 0 for the available parallelism.
+    pub jobs: usize,
+    /// Whether re-exports from other packages are resolved through the `node_modules` of
+    /// the local package, instead of only being recorded.
+    pub resolve_node_modules: bool,
+    /// How changed literal values of exported constants are reported.
+    pub value_change_severity: ChangeSeverity,
+}
+
+impl DiffOptions {
+    /// Applies the options set under the "breakpoint" field of a package.json config.
+    pub fn apply_pkg_config(&mut self, pkg_json: &JsonValue) -> Result<()> {
+        self.apply_tarball_config(&pkg_json["breakpoint"]["tarball"])
+            .with_context(|| "Invalid 'tarball' config.")?;
+
+        let resolve_node_modules = &pkg_json["breakpoint"]["resolveNodeModules"];
+
+        if resolve_node_modules.is_boolean() {
+            self.resolve_node_modules |= resolve_node_modules.as_bool().unwrap();
+        }
+
+        let value_changes = &pkg_json["breakpoint"]["valueChanges"];
+
+        if !value_changes.is_null() {
+            self.value_change_severity = ChangeSeverity::parse(&value_changes.to_string())
+                .with_context(|| "Invalid 'valueChanges' config.")?;
+        }
+
+        let assets_config = &pkg_json["breakpoint"]["assets"];
+
+        if assets_config.is_null() {
+            return Ok(());
+        }
+
+        let compare_contents = &assets_config["compareContents"];
+
+        if compare_contents.is_boolean() {
+            self.compare_asset_contents |= compare_contents.as_bool().unwrap();
+        }
+
+        self.breaking_asset_patterns
+            .extend(Self::get_patterns_in(assets_config, "breaking")?);
+
+        self.informational_asset_patterns
+            .extend(Self::get_patterns_in(assets_config, "informational")?);
+
+        Ok(())
+    }
+
+    fn apply_tarball_config(&mut self, tarball_config: &JsonValue) -> Result<()> {
+        let limits = &mut self.tarball_limits;
+
+        if let Some(max_entry_count) = Self::get_limit_in(tarball_config, "maxEntries")? {
+            limits.max_entry_count = max_entry_count as usize;
+        }
+
+        if let Some(max_file_size) = Self::get_limit_in(tarball_config, "maxFileSize")? {
+            limits.max_file_size = max_file_size;
+        }
+
+        if let Some(max_total_size) = Self::get_limit_in(tarball_config, "maxTotalSize")? {
+            limits.max_total_size = max_total_size;
+        }
+
+        Ok(())
+    }
+
+    fn get_limit_in(config: &JsonValue, field_name: &str) -> Result<Option<u64>> {
+        let limit = &config[field_name];
+
+        if limit.is_null() {
+            return Ok(None);
+        }
+
+        match limit.as_u64() {
+            Some(limit) => Ok(Some(limit)),
+            None => bail!("Expected '{field_name}' to be a positive integer."),
+        }
+    }
+
+    fn get_patterns_in(config: &JsonValue, field_name: &str) -> Result<Vec<Pattern>> {
+        let globs = &config[field_name];
+
+        if globs.is_null() {
+            return Ok(Vec::new());
+        } else if !globs.is_array() {
+            bail!("Expected '{field_name}' asset config to be an array of globs.");
+        }
+
+        let mut patterns = Vec::with_capacity(globs.len());
+
+        for glob in globs.members() {
+            let glob = glob.to_string();
+            let glob = glob.strip_prefix("./").unwrap_or(&glob);
+
+            patterns.push(
+                Pattern::new(glob)
+                    .with_context(|| format!("Invalid '{field_name}' asset glob: {glob}"))?,
+            );
+        }
+
+        Ok(patterns)
+    }
+}
@@ -1,57 +1,191 @@
-use crate::diff::results::DiffResults;
-use crate::pkg::entries::PkgEntryType;
+use crate::diff::results::{ConsistencyResults, DiffResults, ModifiedAsset, PackListDiffResults};
+use crate::ecma::graph::ExportCycle;
+use crate::pkg::packlist::PackListPath;
 use std::process::ExitCode;
 use std::time::Instant;
 
 const TERM_STYLE_BOLD: &str = "\x1b[1m";
 const TERM_STYLE_RED: &str = "\x1b[31m";
+const TERM_STYLE_YELLOW: &str = "\x1b[33m";
 const TERM_STYLE_RESET: &str = "\x1b[0m";
 
 pub fn print_asset_issues(diff_results: &DiffResults) {
-    if !diff_results.removed_assets.is_empty() {
+    let removed_or_moved_count =
+        diff_results.removed_assets.len() + diff_results.moved_assets.len();
+
+    if removed_or_moved_count.gt(&0) {
+        print_breaking_change_tally_header(&removed_or_moved_count, "to assets:".into(), true);
+
+        for missing_asset_path in diff_results.removed_assets.iter() {
+            println!("  - {} was removed.", missing_asset_path.display())
+        }
+
+        for moved_asset in diff_results.moved_assets.iter() {
+            let previous_path = moved_asset.previous_path.display();
+            let current_path = moved_asset.current_path.display();
+
+            let likelihood = if moved_asset.is_identical {
+                "was moved"
+            } else {
+                "was likely moved (same file name)"
+            };
+
+            println!("  - {previous_path} {likelihood} to {current_path}.");
+            println!(
+                "    Consider a compat shim at the previous path, or an exports alias: \"./{previous_path}\": \"./{current_path}\"."
+            )
+        }
+    }
+
+    let (breaking_modified_assets, informational_modified_assets): (Vec<_>, Vec<_>) = diff_results
+        .modified_assets
+        .iter()
+        .partition(|asset| asset.is_breaking);
+
+    if !breaking_modified_assets.is_empty() {
         print_breaking_change_tally_header(
-            &diff_results.removed_assets.len(),
-            "to assets:".into(),
+            &breaking_modified_assets.len(),
+            "to asset contents:".into(),
             true,
         );
 
-        for missing_asset_path in diff_results.removed_assets.iter() {
-            println!("  - {} was removed.", missing_asset_path.display())
+        for asset in breaking_modified_assets {
+            print_modified_asset(asset);
         }
     }
+
+    if !informational_modified_assets.is_empty() {
+        print_tally_header(
+            &informational_modified_assets.len(),
+            ("modified asset", "modified assets"),
+            "(informational):".into(),
+            "",
+        );
+
+        for asset in informational_modified_assets {
+            print_modified_asset(asset);
+        }
+    }
+}
+
+fn print_modified_asset(asset: &ModifiedAsset) {
+    let size_delta = asset.current_size as i64 - asset.previous_size as i64;
+
+    println!(
+        "  - {} was modified ({} -> {} bytes, {:+}).",
+        asset.path.display(),
+        asset.previous_size,
+        asset.current_size,
+        size_delta
+    )
 }
 
 pub fn print_entry_issues(diff_results: &DiffResults) {
     for entry in diff_results.broken_entries.iter() {
         let entry_issue_count = entry.issue_count();
+        let entry_potential_issue_count = entry.potential_issue_count();
 
-        if entry_issue_count.eq(&0) {
-            continue;
-        }
+        let entry_label = if entry.kind.is_single_file_field() {
+            format!("{} entry", entry.kind)
+        } else {
+            format!("{} entry {}", entry.kind, entry.name)
+        };
 
-        match &entry.kind {
-            PkgEntryType::Main => print_breaking_change_tally_header(
-                &entry_issue_count,
-                format!("to {} entry:", entry.kind),
-                true,
-            ),
-            entry_kind => print_breaking_change_tally_header(
+        if entry_issue_count.gt(&0) {
+            print_breaking_change_tally_header(
                 &entry_issue_count,
-                format!("to {} entry {}:", entry_kind, entry.name),
+                format!("to {entry_label}:"),
                 true,
-            ),
+            );
+
+            if entry.is_missing {
+                if let Some(moved_to) = &entry.moved_to {
+                    println!(
+                        "  - was moved to {} ({}).",
+                        moved_to.name,
+                        moved_to.path.display()
+                    );
+
+                    if let Some((subpath, target)) = &moved_to.exports_alias {
+                        println!(
+                            "    Consider keeping an exports alias: \"{subpath}\": \"{target}\"."
+                        )
+                    }
+                } else {
+                    println!("  - was removed.",)
+                }
+            } else {
+                if let Some((previous_path, current_path)) = &entry.retargeted {
+                    println!(
+                        "  - now points to {} instead of {}.",
+                        current_path.display(),
+                        previous_path.display()
+                    )
+                }
+
+                for (export_name, break_type) in entry.broken_exports.iter() {
+                    if !break_type.is_potential() {
+                        println!("  - {export_name} was {break_type}.",)
+                    }
+                }
+            }
         }
 
-        if entry.is_missing {
-            println!("  - was removed.",)
-        } else {
+        // Potential breaks may not affect consumers, so they do not fail the diff.
+        if entry_potential_issue_count.gt(&0) {
+            print_tally_header(
+                &entry_potential_issue_count,
+                ("potential breaking change", "potential breaking changes"),
+                format!("to {entry_label}:"),
+                TERM_STYLE_YELLOW,
+            );
+
             for (export_name, break_type) in entry.broken_exports.iter() {
-                println!("  - {export_name} was {break_type}.",)
+                if break_type.is_potential() {
+                    println!("  - {export_name} was {break_type}.",)
+                }
             }
         }
     }
 }
 
+pub fn print_export_cycles(export_cycles: &[ExportCycle]) {
+    if export_cycles.is_empty() {
+        return;
+    }
+
+    print_tally_header(
+        &export_cycles.len(),
+        ("re-export cycle", "re-export cycles"),
+        "(informational):".into(),
+        "",
+    );
+
+    for export_cycle in export_cycles {
+        let module_paths: Vec<String> = export_cycle
+            .iter()
+            .chain(export_cycle.first())
+            .map(|module_path| module_path.display().to_string())
+            .collect();
+
+        println!("  - {}.", module_paths.join(" -> "))
+    }
+}
+
+pub fn print_types_issues(diff_results: &DiffResults) {
+    for types in diff_results.broken_types.iter() {
+        print_breaking_change_tally_header(
+            &types.breaks.len(),
+            format!("to types of subpath '{}':", types.subpath),
+            true,
+        );
+
+        for break_type in types.breaks.iter() {
+            println!("  - {break_type}.")
+        }
+    }
+}
+
 pub fn print_exit(diff_results: &DiffResults, start_timestamp: Instant) -> ExitCode {
     let issue_count = diff_results.issue_count();
     let elapsed_time = start_timestamp.elapsed().as_secs_f32();
@@ -66,15 +200,156 @@ pub fn print_exit(diff_results: &DiffResults, start_timestamp: Instant) -> ExitC
     }
 }
 
+pub fn print_consistency_warnings(consistency_results: &ConsistencyResults) {
+    for subpath in consistency_results.inconsistent_subpaths.iter() {
+        print_warning_tally_header(
+            &subpath.inconsistent_exports.len(),
+            format!("between entries of subpath '{}':", subpath.subpath),
+        );
+
+        for export in subpath.inconsistent_exports.iter() {
+            println!(
+                "  - {} is missing from {} but present in {}.",
+                export.export_name,
+                export.missing_from.join(", "),
+                export.present_in.join(", ")
+            )
+        }
+    }
+}
+
+pub fn print_consistency_exit(
+    consistency_results: &ConsistencyResults,
+    start_timestamp: Instant,
+) -> ExitCode {
+    let warning_count = consistency_results.warning_count();
+    let elapsed_time = start_timestamp.elapsed().as_secs_f32();
+
+    print_warning_tally_header(&warning_count, format!("in {elapsed_time:.2}s."));
+
+    // Inconsistencies within a single version are reported as warnings only.
+    ExitCode::SUCCESS
+}
+
+pub fn print_pack_list(pack_list_paths: &[PackListPath]) {
+    let (included_paths, excluded_paths): (Vec<_>, Vec<_>) = pack_list_paths
+        .iter()
+        .partition(|pack_list_path| pack_list_path.is_included);
+
+    let total_size: u64 = included_paths.iter().map(|path| path.size).sum();
+
+    print_tally_header(
+        &included_paths.len(),
+        ("packed file", "packed files"),
+        format!("({total_size} bytes):"),
+        "",
+    );
+
+    for included_path in included_paths {
+        println!(
+            "  - {} ({} bytes), {}.",
+            included_path.path.display(),
+            included_path.size,
+            included_path.reason
+        )
+    }
+
+    print_tally_header(
+        &excluded_paths.len(),
+        ("path", "paths"),
+        "excluded from publishing:".into(),
+        "",
+    );
+
+    for excluded_path in excluded_paths {
+        // Excluded directories are listed as a whole.
+        let dir_suffix = if excluded_path.is_dir { "/" } else { "" };
+
+        println!(
+            "  - {}{dir_suffix}, {}.",
+            excluded_path.path.display(),
+            excluded_path.reason
+        )
+    }
+}
+
+pub fn print_pack_list_diff(pack_list_diff_results: &PackListDiffResults) {
+    print_tally_header(
+        &pack_list_diff_results.added_files.len(),
+        ("file", "files"),
+        "added since the last published version:".into(),
+        "",
+    );
+
+    for added_file in pack_list_diff_results.added_files.iter() {
+        println!("  + {}", added_file.display())
+    }
+
+    print_tally_header(
+        &pack_list_diff_results.removed_files.len(),
+        ("file", "files"),
+        "removed since the last published version:".into(),
+        TERM_STYLE_YELLOW,
+    );
+
+    for removed_file in pack_list_diff_results.removed_files.iter() {
+        println!("  - {}", removed_file.display())
+    }
+}
+
+pub fn print_pack_list_exit(
+    pack_list_paths: &[PackListPath],
+    start_timestamp: Instant,
+) -> ExitCode {
+    let packed_file_count = pack_list_paths
+        .iter()
+        .filter(|pack_list_path| pack_list_path.is_included)
+        .count();
+
+    let elapsed_time = start_timestamp.elapsed().as_secs_f32();
+
+    print_tally_header(
+        &packed_file_count,
+        ("packed file", "packed files"),
+        format!("in {elapsed_time:.2}s."),
+        "",
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn print_warning_tally_header(warning_count: &usize, suffix: String) {
+    print_tally_header(
+        warning_count,
+        ("inconsistent export", "inconsistent exports"),
+        suffix,
+        TERM_STYLE_YELLOW,
+    );
+}
+
 fn print_breaking_change_tally_header(issue_count: &usize, suffix: String, is_error: bool) {
-    let prefix = if issue_count.eq(&1) {
-        format!("Found {issue_count} breaking change")
+    let style = if is_error { TERM_STYLE_RED } else { "" };
+
+    print_tally_header(
+        issue_count,
+        ("breaking change", "breaking changes"),
+        suffix,
+        style,
+    );
+}
+
+fn print_tally_header(count: &usize, nouns: (&str, &str), suffix: String, style: &str) {
+    let (noun_singular, noun_plural) = nouns;
+
+    let prefix = if count.eq(&1) {
+        format!("Found {count} {noun_singular}")
     } else {
-        format!("Found {issue_count} breaking changes")
+        format!("Found {count} {noun_plural}")
     };
 
-    let prefix = if is_error {
-        format!("{TERM_STYLE_RED}{prefix}")
+    // Empty tallies are never highlighted.
+    let prefix = if count.gt(&0) {
+        format!("{style}{prefix}")
     } else {
         prefix
     };
@@ -1,4 +1,8 @@
+use crate::ecma::docs::ReleaseTag;
+use crate::ecma::entity::EntityKind;
+use crate::ecma::graph::ExportCycle;
 use crate::pkg::entries::PkgEntryType;
+use crate::pkg::types::TsResolutionMode;
 use std::path::PathBuf;
 use strum_macros::Display;
 
@@ -11,12 +15,127 @@ pub enum BreakType {
     Removed,
     #[strum(serialize = "removed or renamed")]
     RemovedOrRenamed,
+    /// The name is now provided by several `export *` with different bindings, which
+    /// silently drops it from the module's exports.
+    #[strum(to_string = "removed, as `export *` from both {first} and {second} now provide it")]
+    BecameAmbiguous { first: String, second: String },
+    #[strum(to_string = "possibly removed, unless `export *` from {specifiers} provides it")]
+    PossiblyRemoved { specifiers: String },
+    #[strum(to_string = "possibly changed, as it now re-exports {to} instead of {from}")]
+    ExternalSourceChanged { from: String, to: String },
+    #[strum(
+        to_string = "possibly changed, as it is re-exported from '{package_name}', whose version range went from {from} to {to}"
+    )]
+    DependencyRangeChanged {
+        package_name: String,
+        from: String,
+        to: String,
+    },
+    /// The export is used differently, e.g. a function that became a class must now be
+    /// called with `new`.
+    #[strum(to_string = "changed from {from} to {to}")]
+    KindChanged { from: EntityKind, to: EntityKind },
+    /// An optional property that must now be provided.
+    #[strum(serialize = "made required")]
+    BecameRequired,
+    /// A required property that consumers must now provide.
+    #[strum(serialize = "added as required")]
+    AddedAsRequired,
+    /// Some values of the previous type are no longer accepted.
+    #[strum(to_string = "narrowed from {from} to {to}")]
+    TypeNarrowed { from: String, to: String },
+    /// A required property that may now be missing from values consumers receive.
+    #[strum(serialize = "made optional")]
+    BecameOptional,
+    /// Values consumers receive may no longer be of the previous type.
+    #[strum(to_string = "widened from {from} to {to}")]
+    TypeWidened { from: String, to: String },
+    /// The previous and current types are unrelated, so neither is a subtype of the other.
+    #[strum(to_string = "changed from type {from} to {to}")]
+    TypeChanged { from: String, to: String },
+    #[strum(serialize = "added without a default")]
+    AddedWithoutDefault,
+    /// The literal value of an exported constant or enum member changed. Constants are
+    /// only breaking when configured to be.
+    #[strum(to_string = "changed from {from} to {to}")]
+    ValueChanged {
+        from: String,
+        to: String,
+        is_breaking: bool,
+    },
+    /// An export removed without a previous release deprecating it, which leaves consumers
+    /// no time to migrate.
+    #[strum(to_string = "{change} without being deprecated first")]
+    WithoutDeprecation { change: Box<BreakType> },
+    /// A change to an export tagged as not yet stable, which consumers use at their own risk.
+    #[strum(to_string = "{change}, but is tagged {release_tag}")]
+    PreRelease {
+        change: Box<BreakType>,
+        release_tag: ReleaseTag,
+    },
+}
+
+impl BreakType {
+    /// Whether the change may not be breaking, as it depends on exports of other packages
+    /// that were not analyzed, or on how consumers use the value.
+    pub fn is_potential(&self) -> bool {
+        match self {
+            BreakType::PossiblyRemoved { .. }
+            | BreakType::ExternalSourceChanged { .. }
+            | BreakType::DependencyRangeChanged { .. } => true,
+            BreakType::ValueChanged { is_breaking, .. } => !is_breaking,
+            BreakType::WithoutDeprecation { change } => change.is_potential(),
+            BreakType::PreRelease { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Display, Debug)]
+pub enum TypesBreakType {
+    #[strum(serialize = "Types were removed")]
+    Removed,
+    #[strum(to_string = "Types no longer resolve with TypeScript {versions} under {mode}")]
+    Unresolved {
+        mode: TsResolutionMode,
+        versions: String,
+    },
+    #[strum(to_string = "typesVersions no longer covers TypeScript {versions}")]
+    TypesVersionsUncovered { versions: String },
+}
+
+pub struct BrokenTypesResult {
+    pub subpath: String,
+    pub breaks: Vec<TypesBreakType>,
+}
+
+pub struct ModifiedAsset {
+    pub path: PathBuf,
+    pub previous_size: usize,
+    pub current_size: usize,
+    pub is_breaking: bool,
+}
+
+pub struct MovedAsset {
+    pub previous_path: PathBuf,
+    pub current_path: PathBuf,
+    /// Whether the contents are identical, as opposed to only the file name matching.
+    pub is_identical: bool,
+}
+
+pub struct MovedEntry {
+    pub name: String,
+    pub path: PathBuf,
+    /// An exports field alias (subpath & target) that keeps the previous subpath working.
+    pub exports_alias: Option<(String, String)>,
 }
 
 pub struct BrokenEntryResult {
     pub kind: PkgEntryType,
     pub name: String,
     pub is_missing: bool,
+    pub retargeted: Option<(PathBuf, PathBuf)>,
+    pub moved_to: Option<MovedEntry>,
     pub broken_exports: Vec<BrokenExport>,
 }
 
@@ -25,15 +144,30 @@ impl BrokenEntryResult {
         if self.is_missing {
             1
         } else {
-            self.broken_exports.len()
+            self.broken_exports
+                .iter()
+                .filter(|(_, break_type)| !break_type.is_potential())
+                .count()
         }
     }
+
+    pub fn potential_issue_count(&self) -> usize {
+        self.broken_exports
+            .iter()
+            .filter(|(_, break_type)| break_type.is_potential())
+            .count()
+    }
 }
 
 #[derive(Default)]
 pub struct DiffResults {
     pub removed_assets: Vec<PathBuf>,
+    pub modified_assets: Vec<ModifiedAsset>,
+    pub moved_assets: Vec<MovedAsset>,
     pub broken_entries: Vec<BrokenEntryResult>,
+    pub broken_types: Vec<BrokenTypesResult>,
+    /// Re-export cycles within the current package, which are reported but not breaking.
+    pub export_cycles: Vec<ExportCycle>,
 }
 
 impl DiffResults {
@@ -44,6 +178,56 @@ impl DiffResults {
             .map(|entry| entry.issue_count())
             .sum::<usize>();
 
-        self.removed_assets.len() + broken_entry_issue_count
+        let broken_types_issue_count = self
+            .broken_types
+            .iter()
+            .map(|types| types.breaks.len())
+            .sum::<usize>();
+
+        let modified_assets_issue_count = self
+            .modified_assets
+            .iter()
+            .filter(|asset| asset.is_breaking)
+            .count();
+
+        self.removed_assets.len()
+            + self.moved_assets.len()
+            + modified_assets_issue_count
+            + broken_entry_issue_count
+            + broken_types_issue_count
     }
 }
+
+pub struct InconsistentExport {
+    pub export_name: String,
+    pub present_in: Vec<String>,
+    pub missing_from: Vec<String>,
+}
+
+pub struct InconsistentSubpathResult {
+    pub subpath: String,
+    pub inconsistent_exports: Vec<InconsistentExport>,
+}
+
+#[derive(Default)]
+pub struct ConsistencyResults {
+    pub inconsistent_subpaths: Vec<InconsistentSubpathResult>,
+    pub export_cycles: Vec<ExportCycle>,
+}
+
+impl ConsistencyResults {
+    pub fn warning_count(&self) -> usize {
+        self.inconsistent_subpaths
+            .iter()
+            .map(|subpath| subpath.inconsistent_exports.len())
+            .sum::<usize>()
+    }
+}
+
+/// Differences between the files a local package would publish, and the files of its
+/// last published version.
+#[derive(Default)]
+pub struct PackListDiffResults {
+    pub added_files: Vec<PathBuf>,
+    pub removed_files: Vec<PathBuf>,
+}
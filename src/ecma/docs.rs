@@ -0,0 +1,131 @@
+use strum_macros::Display;
+use swc_common::comments::{CommentKind, Comments, SingleThreadedComments};
+use swc_common::BytePos;
+
+/// How stable an export is declared to be, with a TSDoc release tag. Untagged exports
+/// are public.
+#[derive(Display, Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReleaseTag {
+    #[default]
+    #[strum(serialize = "@public")]
+    Public,
+    #[strum(serialize = "@beta")]
+    Beta,
+    #[strum(serialize = "@alpha")]
+    Alpha,
+    /// Not part of the API, even though it's exported.
+    #[strum(serialize = "@internal")]
+    Internal,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DocTags {
+    /// The release tag, if the declaration has one. Namespace members without one have
+    /// that of their namespace.
+    pub release_tag: Option<ReleaseTag>,
+    pub is_deprecated: bool,
+}
+
+/// The tags of the doc comment of a declaration, i.e. the last `/** */` comment leading
+/// the position it starts at.
+pub fn get_doc_tags(comments: &SingleThreadedComments, position: BytePos) -> DocTags {
+    let mut doc_tags = DocTags::default();
+
+    let leading_comments = comments.get_leading(position).unwrap_or_default();
+    let doc_comment = leading_comments
+        .iter()
+        .rev()
+        .find(|comment| comment.kind.eq(&CommentKind::Block) && comment.text.starts_with('*'));
+
+    let Some(doc_comment) = doc_comment else {
+        return doc_tags;
+    };
+
+    // Block tags start a line, after its leading `*`, so those mentioned in prose are not.
+    for line in doc_comment.text.lines() {
+        let tag = line
+            .trim_start()
+            .trim_start_matches('*')
+            .split_whitespace()
+            .next();
+
+        match tag.unwrap_or_default() {
+            "@public" => doc_tags.release_tag = Some(ReleaseTag::Public),
+            "@beta" => doc_tags.release_tag = Some(ReleaseTag::Beta),
+            "@alpha" => doc_tags.release_tag = Some(ReleaseTag::Alpha),
+            "@internal" => doc_tags.release_tag = Some(ReleaseTag::Internal),
+            "@deprecated" => doc_tags.is_deprecated = true,
+            _ => {}
+        }
+    }
+
+    doc_tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecma::parser::parse_module_source;
+    use std::path::Path;
+    use swc_common::Spanned;
+
+    /// The tags of the first statement of the source.
+    fn get_first_doc_tags(source: &str) -> DocTags {
+        let (module, comments) = parse_module_source(Path::new("index.ts"), source.into()).unwrap();
+
+        get_doc_tags(&comments, module.body[0].span_lo())
+    }
+
+    #[test]
+    fn tags_start_a_line_of_the_doc_comment() {
+        let doc_tags = get_first_doc_tags(
+            r#"
+            /**
+             * Runs the task.
+             *
+             * @deprecated Use `start` instead.
+             * @beta
+             */
+            export function run() {}
+            "#,
+        );
+
+        assert_eq!(doc_tags.release_tag, Some(ReleaseTag::Beta));
+        assert!(doc_tags.is_deprecated);
+
+        let doc_tags = get_first_doc_tags("/** @internal */ export function run() {}");
+
+        assert_eq!(doc_tags.release_tag, Some(ReleaseTag::Internal));
+    }
+
+    #[test]
+    fn tags_mentioned_in_prose_are_ignored() {
+        let doc_tags = get_first_doc_tags(
+            r#"
+            /**
+             * Unlike the @internal helpers, this is public, and not @deprecated.
+             * See {@link start} for the @beta version.
+             */
+            export function run() {}
+            "#,
+        );
+
+        assert_eq!(doc_tags, DocTags::default());
+    }
+
+    #[test]
+    fn only_the_last_doc_comment_is_read() {
+        let doc_tags = get_first_doc_tags(
+            r#"
+            /** @alpha */
+            /* @internal */
+            // @deprecated
+            /** @beta */
+            export function run() {}
+            "#,
+        );
+
+        assert_eq!(doc_tags.release_tag, Some(ReleaseTag::Beta));
+        assert!(!doc_tags.is_deprecated);
+    }
+}
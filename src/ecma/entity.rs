@@ -1,11 +1,57 @@
+use crate::ecma::types::{get_function_type, FunctionType, TypeDeclaration};
+use crate::ecma::value::{get_constant_value, ConstantValue};
 use anyhow::{bail, Result};
-use swc_ecma_ast::{Class, ClassDecl, DefaultDecl, ExportDecl, FnDecl, Function, VarDeclarator};
+use strum_macros::Display;
+use swc_ecma_ast::{
+    BindingIdent, Class, ClassDecl, Decl, DefaultDecl, ExportDecl, ExportDefaultExpr, Expr, FnDecl,
+    Function, Ident, ObjectPatProp, Pat, Prop, PropName, PropOrSpread, TsEnumDecl,
+    TsExportAssignment, TsInterfaceDecl, TsModuleDecl, TsModuleName, TsTypeAliasDecl, VarDecl,
+    VarDeclarator,
+};
 
 #[derive(Clone)]
 pub enum EntityDeclaration {
     Var(String, VarDeclarator),
     Class(String, Box<Class>),
     Func(String, Box<Function>),
+    /// A function declared with several overload signatures, without its implementation.
+    Overloads(String, Vec<Function>),
+    /// An expression exported without a declaration, e.g. `export default { a, b }`.
+    Expr(String, Box<Expr>),
+    Enum(String, Box<TsEnumDecl>),
+    Interface(String, Box<TsInterfaceDecl>),
+    TypeAlias(String, Box<TsTypeAliasDecl>),
+    /// A TypeScript namespace, whose members are exported as entities of their own.
+    Namespace(String),
+}
+
+/// What an export is at runtime, which determines how consumers can use it.
+#[derive(Display, Debug, Clone, Copy, PartialEq)]
+pub enum EntityKind {
+    #[strum(serialize = "a function")]
+    Function,
+    #[strum(serialize = "a class")]
+    Class,
+    #[strum(serialize = "a value")]
+    Value,
+    #[strum(serialize = "an enum")]
+    Enum,
+    /// Inlined by the compiler, so it has no runtime value with `isolatedModules`.
+    #[strum(serialize = "a const enum")]
+    ConstEnum,
+    /// Declared as provided by something else, so it's not emitted.
+    #[strum(serialize = "a declared enum")]
+    DeclaredEnum,
+    #[strum(serialize = "a declared const enum")]
+    DeclaredConstEnum,
+    /// An interface or type alias, which only exists for TypeScript consumers.
+    #[strum(serialize = "a type")]
+    Type,
+    #[strum(serialize = "a namespace")]
+    Namespace,
+    /// The entity is computed, e.g. returned by a call, so its kind is unknown.
+    #[strum(serialize = "an unknown kind")]
+    Unknown,
 }
 
 pub trait AsEntityDeclaration {
@@ -22,39 +68,168 @@ impl EntityDeclaration {
             EntityDeclaration::Var(name, _) => name,
             EntityDeclaration::Class(name, _) => name,
             EntityDeclaration::Func(name, _) => name,
+            EntityDeclaration::Overloads(name, _) => name,
+            EntityDeclaration::Expr(name, _) => name,
+            EntityDeclaration::Enum(name, _) => name,
+            EntityDeclaration::Interface(name, _) => name,
+            EntityDeclaration::TypeAlias(name, _) => name,
+            EntityDeclaration::Namespace(name) => name,
+        }
+    }
+
+    pub fn kind(&self) -> EntityKind {
+        let expression = match self {
+            EntityDeclaration::Func(..) | EntityDeclaration::Overloads(..) => {
+                return EntityKind::Function
+            }
+            EntityDeclaration::Class(..) => return EntityKind::Class,
+            EntityDeclaration::Namespace(..) => return EntityKind::Namespace,
+            EntityDeclaration::Interface(..) | EntityDeclaration::TypeAlias(..) => {
+                return EntityKind::Type
+            }
+            EntityDeclaration::Enum(_, declaration) => {
+                return match (declaration.declare, declaration.is_const) {
+                    (false, false) => EntityKind::Enum,
+                    (false, true) => EntityKind::ConstEnum,
+                    (true, false) => EntityKind::DeclaredEnum,
+                    (true, true) => EntityKind::DeclaredConstEnum,
+                }
+            }
+            EntityDeclaration::Var(_, declarator) => match &declarator.init {
+                Some(init) => init.unwrap_parens(),
+                None => return EntityKind::Unknown,
+            },
+            EntityDeclaration::Expr(_, expression) => expression.unwrap_parens(),
+        };
+
+        match expression {
+            Expr::Fn(_) | Expr::Arrow(_) => EntityKind::Function,
+            Expr::Class(_) => EntityKind::Class,
+            Expr::Lit(_) | Expr::Tpl(_) | Expr::Object(_) | Expr::Array(_) | Expr::New(_) => {
+                EntityKind::Value
+            }
+            _ if get_constant_value(expression).is_some() => EntityKind::Value,
+            _ => EntityKind::Unknown,
+        }
+    }
+
+    /// Declares the entity again under the same name, as function overloads & namespace
+    /// merges do. Consumers only see the overload signatures of a function, so its
+    /// implementation is left out.
+    pub fn merge(self, declaration: EntityDeclaration) -> EntityDeclaration {
+        match (self, declaration) {
+            // Functions, classes & enums merged with a namespace remain what they are.
+            (existing, EntityDeclaration::Namespace(..)) => existing,
+            (EntityDeclaration::Func(name, existing), EntityDeclaration::Func(_, function))
+                if existing.body.is_none() =>
+            {
+                if function.body.is_some() {
+                    EntityDeclaration::Func(name, existing)
+                } else {
+                    EntityDeclaration::Overloads(name, vec![*existing, *function])
+                }
+            }
+            (
+                EntityDeclaration::Overloads(name, mut overloads),
+                EntityDeclaration::Func(_, function),
+            ) => {
+                if function.body.is_none() {
+                    overloads.push(*function);
+                }
+
+                EntityDeclaration::Overloads(name, overloads)
+            }
+            (_, declaration) => declaration,
+        }
+    }
+
+    /// The entity as seen from a declaration file, where `declare` only restates that it's
+    /// ambient, as the file describes a module emitted elsewhere.
+    pub fn in_declaration_file(self) -> EntityDeclaration {
+        match self {
+            EntityDeclaration::Enum(name, mut declaration) => {
+                declaration.declare = false;
+                EntityDeclaration::Enum(name, declaration)
+            }
+            declaration => declaration,
+        }
+    }
+
+    /// The signatures the entity can be called with, if it's a function declaration.
+    pub fn signatures(&self) -> Vec<FunctionType> {
+        match self {
+            EntityDeclaration::Func(_, function) => vec![get_function_type(function)],
+            EntityDeclaration::Overloads(_, overloads) => {
+                overloads.iter().map(get_function_type).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The structure of the entity, if it's an interface or type alias.
+    pub fn type_declaration(&self) -> Option<TypeDeclaration> {
+        match self {
+            EntityDeclaration::Interface(_, interface) => {
+                Some(TypeDeclaration::from_interface(interface))
+            }
+            EntityDeclaration::TypeAlias(_, type_alias) => {
+                Some(TypeDeclaration::from_type_alias(type_alias))
+            }
+            _ => None,
+        }
+    }
+
+    /// The value the entity is initialized with, if it's a literal.
+    pub fn constant_value(&self) -> Option<ConstantValue> {
+        match self {
+            EntityDeclaration::Var(_, declarator) => get_constant_value(declarator.init.as_ref()?),
+            EntityDeclaration::Expr(_, expression) => get_constant_value(expression),
+            _ => None,
         }
     }
 }
 
+pub fn get_object_property_name(property: &PropOrSpread) -> Option<String> {
+    let property = property.as_prop()?;
+
+    let key = match property.as_ref() {
+        Prop::Shorthand(ident) => return Some(ident.sym.to_string()),
+        Prop::KeyValue(property) => &property.key,
+        Prop::Method(property) => &property.key,
+        Prop::Getter(property) => &property.key,
+        _ => return None,
+    };
+
+    match key {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(name) => Some(name.value.to_string()),
+        _ => None,
+    }
+}
+
 impl AsEntityDeclaration for &ExportDecl {
     fn from(self) -> Result<EntityDeclaration> {
-        if self.decl.is_fn_decl() {
-            let declaration = self.decl.as_fn_decl().unwrap();
-            let name = declaration.ident.sym.to_string();
-            let function = declaration.function.to_owned();
-
-            Ok(EntityDeclaration::Func(name, function))
-        } else if self.decl.is_class() {
-            let declaration = self.decl.as_class().unwrap();
-            let name = declaration.ident.sym.to_string();
-            let class = declaration.class.to_owned();
+        EntityDeclaration::from(&self.decl)
+    }
+}
 
-            Ok(EntityDeclaration::Class(name, class))
-        } else if self.decl.is_var() {
-            let expression = self.decl.as_var().unwrap();
-            let declarator = expression.decls.last().unwrap();
-            let name = declarator.name.as_ident().unwrap().sym.to_string();
-
-            Ok(EntityDeclaration::Var(name, declarator.to_owned()))
-        } else if self.decl.is_ts_interface() {
-            todo!("handle TS interface entity declaration")
-        } else if self.decl.is_ts_enum() {
-            todo!("handle TS enum entity declaration")
-        } else if self.decl.is_ts_module() {
-            todo!("handle TS module entity declaration")
-        } else if self.decl.is_ts_type_alias() {
-            todo!("handle TS type alias entity declaration")
-        } else if self.decl.is_using() {
+impl AsEntityDeclaration for &Decl {
+    fn from(self) -> Result<EntityDeclaration> {
+        if self.is_fn_decl() {
+            EntityDeclaration::from(self.as_fn_decl().unwrap())
+        } else if self.is_class() {
+            EntityDeclaration::from(self.as_class().unwrap())
+        } else if self.is_var() {
+            bail!("Variable exports can declare several entities, see get_var_declarations.")
+        } else if self.is_ts_interface() {
+            EntityDeclaration::from(self.as_ts_interface().unwrap().as_ref())
+        } else if self.is_ts_enum() {
+            EntityDeclaration::from(self.as_ts_enum().unwrap().as_ref())
+        } else if self.is_ts_module() {
+            EntityDeclaration::from(self.as_ts_module().unwrap().as_ref())
+        } else if self.is_ts_type_alias() {
+            EntityDeclaration::from(self.as_ts_type_alias().unwrap().as_ref())
+        } else if self.is_using() {
             todo!("handle using entity declaration")
         } else {
             bail!("Unsupported ExportDecl entity.")
@@ -64,31 +239,147 @@ impl AsEntityDeclaration for &ExportDecl {
 
 impl AsEntityDeclaration for &DefaultDecl {
     fn from(self) -> Result<EntityDeclaration> {
+        // Anonymous functions & classes are named "default", as they would be at runtime.
         if self.is_fn_expr() {
             let expression = self.as_fn_expr().unwrap();
-            let name = expression.ident.as_ref().unwrap().sym.to_string();
+            let name = get_default_name(expression.ident.as_ref());
             let function = expression.function.to_owned();
 
             Ok(EntityDeclaration::Func(name, function))
         } else if self.is_class() {
             let expression = self.as_class().unwrap();
-            let name = expression.ident.as_ref().unwrap().sym.to_string();
+            let name = get_default_name(expression.ident.as_ref());
             let class = expression.class.to_owned();
 
             Ok(EntityDeclaration::Class(name, class))
         } else if self.is_ts_interface_decl() {
-            todo!("handle TS interface entity declaration")
+            EntityDeclaration::from(self.as_ts_interface_decl().unwrap().as_ref())
         } else {
             bail!("Unsupported DefaultDecl entity.")
         }
     }
 }
 
-impl AsEntityDeclaration for &VarDeclarator {
+impl AsEntityDeclaration for &ExportDefaultExpr {
     fn from(self) -> Result<EntityDeclaration> {
-        Ok(EntityDeclaration::Var(
-            self.name.as_ident().unwrap().sym.to_string(),
-            self.to_owned(),
+        let expression = self.expr.unwrap_parens();
+
+        if let Some(function) = expression.as_fn_expr() {
+            let name = get_default_name(function.ident.as_ref());
+
+            Ok(EntityDeclaration::Func(name, function.function.to_owned()))
+        } else if let Some(class) = expression.as_class() {
+            let name = get_default_name(class.ident.as_ref());
+
+            Ok(EntityDeclaration::Class(name, class.class.to_owned()))
+        } else {
+            Ok(EntityDeclaration::Expr(
+                "default".into(),
+                Box::new(expression.to_owned()),
+            ))
+        }
+    }
+}
+
+impl AsEntityDeclaration for &TsExportAssignment {
+    fn from(self) -> Result<EntityDeclaration> {
+        Ok(EntityDeclaration::Expr(
+            "default".into(),
+            Box::new(self.expr.unwrap_parens().to_owned()),
+        ))
+    }
+}
+
+/// Every binding declared by a variable statement, including those of destructuring
+/// patterns. Destructured bindings are kept without an initializer, as it isn't their value.
+pub fn get_var_declarations(var: &VarDecl) -> Vec<EntityDeclaration> {
+    let mut declarations = Vec::new();
+
+    for declarator in var.decls.iter() {
+        if let Some(binding) = declarator.name.as_ident() {
+            let name = binding.sym.to_string();
+
+            declarations.push(EntityDeclaration::Var(name, declarator.to_owned()));
+            continue;
+        }
+
+        let mut bindings = Vec::new();
+        add_pattern_bindings(&declarator.name, &mut bindings);
+
+        for binding in bindings {
+            let name = binding.sym.to_string();
+            let declarator = VarDeclarator {
+                span: declarator.span,
+                name: Pat::Ident(binding),
+                init: None,
+                definite: false,
+            };
+
+            declarations.push(EntityDeclaration::Var(name, declarator));
+        }
+    }
+
+    declarations
+}
+
+fn add_pattern_bindings(pattern: &Pat, bindings: &mut Vec<BindingIdent>) {
+    match pattern {
+        Pat::Ident(binding) => bindings.push(binding.to_owned()),
+        Pat::Array(array) => {
+            for element in array.elems.iter().flatten() {
+                add_pattern_bindings(element, bindings);
+            }
+        }
+        Pat::Object(object) => {
+            for property in object.props.iter() {
+                match property {
+                    ObjectPatProp::KeyValue(property) => {
+                        add_pattern_bindings(&property.value, bindings)
+                    }
+                    ObjectPatProp::Assign(property) => bindings.push(property.key.to_owned()),
+                    ObjectPatProp::Rest(rest) => add_pattern_bindings(&rest.arg, bindings),
+                }
+            }
+        }
+        Pat::Rest(rest) => add_pattern_bindings(&rest.arg, bindings),
+        Pat::Assign(assign) => add_pattern_bindings(&assign.left, bindings),
+        Pat::Invalid(_) | Pat::Expr(_) => {}
+    }
+}
+
+impl AsEntityDeclaration for &TsEnumDecl {
+    fn from(self) -> Result<EntityDeclaration> {
+        Ok(EntityDeclaration::Enum(
+            self.id.sym.to_string(),
+            Box::new(self.to_owned()),
+        ))
+    }
+}
+
+impl AsEntityDeclaration for &TsModuleDecl {
+    fn from(self) -> Result<EntityDeclaration> {
+        let TsModuleName::Ident(ident) = &self.id else {
+            bail!("Ambient module declarations are not entities.")
+        };
+
+        Ok(EntityDeclaration::Namespace(ident.sym.to_string()))
+    }
+}
+
+impl AsEntityDeclaration for &TsInterfaceDecl {
+    fn from(self) -> Result<EntityDeclaration> {
+        Ok(EntityDeclaration::Interface(
+            self.id.sym.to_string(),
+            Box::new(self.to_owned()),
+        ))
+    }
+}
+
+impl AsEntityDeclaration for &TsTypeAliasDecl {
+    fn from(self) -> Result<EntityDeclaration> {
+        Ok(EntityDeclaration::TypeAlias(
+            self.id.sym.to_string(),
+            Box::new(self.to_owned()),
         ))
     }
 }
@@ -110,3 +401,9 @@ impl AsEntityDeclaration for &ClassDecl {
         ))
     }
 }
+
+fn get_default_name(ident: Option<&Ident>) -> String {
+    ident
+        .map(|ident| ident.sym.to_string())
+        .unwrap_or("default".into())
+}
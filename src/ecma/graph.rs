@@ -0,0 +1,945 @@
+use crate::ecma::entity::EntityDeclaration;
+use crate::ecma::parser::parse_module;
+use crate::ecma::walker::{get_module_record, Declarations, ExportDocTags};
+use crate::fs::path::normalize_relative_path;
+use crate::pkg::contents::PkgContents;
+use crate::pkg::entries::{PkgEntries, PkgEntry};
+use crate::pkg::registry::load_entries_from_dir;
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Extensions tried, in order, for relative imports that omit them.
+const IMPORT_EXTENSIONS: [&str; 8] = ["js", "mjs", "cjs", "jsx", "ts", "mts", "cts", "tsx"];
+
+/// TypeScript sources import their compiled output's extension, e.g. "./a.js" for
+/// "./a.ts".
+const TS_SOURCE_EXTENSIONS: [(&str, &[&str]); 3] =
+    [("js", &["ts", "tsx"]), ("mjs", &["mts"]), ("cjs", &["cts"])];
+
+/// Declaration files import the extension of the module they describe as well, e.g.
+/// "./a.js" for "./a.d.ts".
+const DECLARATION_EXTENSIONS: [(&str, &str); 3] =
+    [("js", "d.ts"), ("mjs", "d.mts"), ("cjs", "d.cts")];
+
+#[derive(Default)]
+pub struct ModuleExports {
+    pub default_export: Option<EntityDeclaration>,
+    pub named_exports: Declarations,
+    /// Names that several `export *` provide with different bindings, which are therefore
+    /// not exported, with the origins of two of the conflicting bindings.
+    pub ambiguous_exports: HashMap<String, (String, String)>,
+    /// Re-exports from other packages that were not resolved, by exported name.
+    pub external_exports: HashMap<String, ExternalExport>,
+    /// Specifiers of other packages re-exported with `export *` that were not resolved,
+    /// whose names are therefore unknown.
+    pub external_star_exports: BTreeSet<String>,
+    /// The UMD global the module's exports are also available as.
+    pub global_name: Option<String>,
+    /// TSDoc tags of the exports, e.g. their release tag.
+    pub doc_tags: ExportDocTags,
+}
+
+/// The exports of a single module, before the modules it imports from are linked.
+#[derive(Default)]
+pub struct ModuleRecord {
+    /// Exports of bindings declared in the module, including "default".
+    pub local_exports: Declarations,
+    /// Exports of bindings imported from other modules.
+    pub indirect_exports: Vec<IndirectExport>,
+    /// Specifiers of the modules re-exported with `export *`.
+    pub star_exports: Vec<String>,
+    /// The global a UMD declaration file exposes its exports as, with `export as namespace`.
+    pub global_name: Option<String>,
+    /// TSDoc tags of the exports, e.g. their release tag.
+    pub doc_tags: ExportDocTags,
+}
+
+pub struct IndirectExport {
+    pub exported_name: String,
+    pub specifier: String,
+    pub import_name: ImportName,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum ImportName {
+    Named(String),
+    Namespace,
+}
+
+/// A binding re-exported from another package, e.g. `export { x } from "pkg/utils"`.
+#[derive(Clone, PartialEq)]
+pub struct ExternalExport {
+    pub package_name: String,
+    pub specifier: String,
+    pub import_name: ImportName,
+}
+
+/// A chain of modules re-exporting from each other, starting from its first module in
+/// path order and implicitly closed by it.
+pub type ExportCycle = Vec<PathBuf>;
+
+/// The parsed records and linked exports of each module of a package that has been
+/// walked, keyed by its path relative to the package root. Modules shared by several
+/// entries, such as barrels, are parsed and walked only once.
+#[derive(Default)]
+pub struct ModuleCache {
+    records: Mutex<HashMap<PathBuf, Arc<LinkedRecord>>>,
+    exports: Mutex<HashMap<PathBuf, Arc<ModuleExports>>>,
+    cycles: Mutex<BTreeSet<ExportCycle>>,
+    /// Entries of the installed packages imported by name, or `None` when not installed.
+    dependencies: Mutex<HashMap<String, Option<Arc<PkgEntries>>>>,
+}
+
+/// A module record with the import specifiers it uses resolved to package modules.
+struct LinkedRecord {
+    record: ModuleRecord,
+    import_paths: HashMap<String, Option<PathBuf>>,
+}
+
+/// What an export name of a module refers to, per the ES spec's `ResolveExport`.
+#[derive(PartialEq)]
+enum ExportResolution {
+    Binding { module_path: PathBuf, name: String },
+    Namespace(PathBuf),
+    External(ExternalExport),
+    Ambiguous(String, String),
+    Missing,
+}
+
+impl ExportResolution {
+    /// The module that declares the binding or is re-exported as a namespace, or the
+    /// specifier of the other package it is re-exported from.
+    fn origin(&self) -> Option<String> {
+        match self {
+            ExportResolution::Binding { module_path, .. } => {
+                Some(module_path.display().to_string())
+            }
+            ExportResolution::Namespace(module_path) => Some(module_path.display().to_string()),
+            ExportResolution::External(export) => Some(format!("'{}'", export.specifier)),
+            _ => None,
+        }
+    }
+}
+
+/// Walks modules of a package through their re-exports, from a single entry.
+pub struct ModuleGraph<'pkg> {
+    contents: &'pkg PkgContents,
+    namespace_stack: Vec<PathBuf>,
+    /// The outermost module of `namespace_stack` that a namespace re-export cycled back
+    /// to, whose exports were not known yet.
+    namespace_cycle_depth: Option<usize>,
+    /// Whether imports of other packages are resolved through `node_modules`.
+    resolve_node_modules: bool,
+}
+
+impl ModuleCache {
+    /// Re-export cycles found so far, in path order.
+    pub fn get_cycles(&self) -> Vec<ExportCycle> {
+        self.cycles.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn insert_cycle(&self, module_paths: &[PathBuf]) {
+        let first_index = module_paths
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, module_path)| *module_path)
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+
+        let mut cycle = module_paths.to_vec();
+        cycle.rotate_left(first_index);
+
+        self.cycles.lock().unwrap().insert(cycle);
+    }
+}
+
+impl<'pkg> ModuleGraph<'pkg> {
+    pub fn new(contents: &'pkg PkgContents, resolve_node_modules: bool) -> Self {
+        Self {
+            contents,
+            namespace_stack: Vec::new(),
+            namespace_cycle_depth: None,
+            resolve_node_modules,
+        }
+    }
+
+    pub fn get_exports_of(&mut self, module_path: &Path) -> Result<Arc<ModuleExports>> {
+        let module_path = normalize_relative_path(module_path)
+            .with_context(|| format!("Invalid module path: {}", module_path.display()))?;
+
+        let module_cache = self.contents.module_cache();
+
+        if let Some(module_exports) = module_cache.exports.lock().unwrap().get(&module_path) {
+            return Ok(Arc::clone(module_exports));
+        }
+
+        // Only namespace re-exports can walk back into a module whose exports are being
+        // linked; `export *` and named re-exports are resolved name by name.
+        if let Some(depth) = self
+            .namespace_stack
+            .iter()
+            .position(|stack_path| stack_path.eq(&module_path))
+        {
+            module_cache.insert_cycle(&self.namespace_stack[depth..]);
+
+            self.namespace_cycle_depth = Some(
+                self.namespace_cycle_depth
+                    .map_or(depth, |cycle_depth| cycle_depth.min(depth)),
+            );
+
+            return Ok(Arc::new(ModuleExports::default()));
+        }
+
+        let depth = self.namespace_stack.len();
+
+        self.namespace_stack.push(module_path.to_owned());
+        let module_exports = self.link_exports_of(&module_path);
+        self.namespace_stack.pop();
+
+        let module_exports = Arc::new(module_exports?);
+
+        // Exports linked while an outer module was still being walked are incomplete, and
+        // are only valid from that outer module.
+        let is_complete = self
+            .namespace_cycle_depth
+            .is_none_or(|cycle_depth| cycle_depth.ge(&depth));
+
+        if self.namespace_cycle_depth.eq(&Some(depth)) {
+            self.namespace_cycle_depth = None;
+        }
+
+        if is_complete {
+            module_cache
+                .exports
+                .lock()
+                .unwrap()
+                .insert(module_path, Arc::clone(&module_exports));
+        }
+
+        Ok(module_exports)
+    }
+
+    /// Resolves an import specifier to the path of a package module. Bare specifiers,
+    /// which refer to other packages, resolve to `None`.
+    pub fn resolve_import(&self, importer_path: &Path, specifier: &str) -> Result<Option<PathBuf>> {
+        if !specifier.starts_with("./") && !specifier.starts_with("../") {
+            return Ok(None);
+        }
+
+        let importer_dir = importer_path.parent().unwrap_or(Path::new(""));
+
+        let import_path = match normalize_relative_path(&importer_dir.join(specifier)) {
+            Some(import_path) => import_path,
+            None => bail!("Import '{specifier}' points outside of the package."),
+        };
+
+        let candidate_paths = if is_declaration_file(importer_path) {
+            get_declaration_import_candidates(&import_path)
+        } else {
+            get_import_candidates(&import_path)
+        };
+
+        for candidate_path in candidate_paths {
+            if self.contents.has_file(&candidate_path)? {
+                return Ok(Some(candidate_path));
+            }
+        }
+
+        bail!(
+            "Failed to resolve import '{specifier}' from: {}",
+            importer_path.display()
+        )
+    }
+
+    fn link_exports_of(&mut self, module_path: &Path) -> Result<ModuleExports> {
+        let mut module_exports = ModuleExports {
+            global_name: self
+                .get_record_of(module_path)?
+                .record
+                .global_name
+                .to_owned(),
+            ..Default::default()
+        };
+
+        let export_names = self.get_exported_names(
+            module_path,
+            &mut HashSet::new(),
+            &mut Vec::new(),
+            &mut module_exports.external_star_exports,
+        )?;
+
+        for export_name in export_names {
+            let resolution = self.resolve_export(
+                module_path,
+                &export_name,
+                &mut HashSet::new(),
+                &mut Vec::new(),
+            )?;
+
+            match resolution {
+                ExportResolution::Binding { module_path, name } => {
+                    let binding_record = self.get_record_of(&module_path)?;
+                    let declaration = binding_record.record.local_exports[&name].to_owned();
+
+                    if let Some(doc_tags) = binding_record.record.doc_tags.get(&name) {
+                        module_exports
+                            .doc_tags
+                            .insert(export_name.to_owned(), *doc_tags);
+                    }
+
+                    if export_name.eq("default") {
+                        module_exports.default_export = Some(declaration);
+                    } else {
+                        module_exports
+                            .named_exports
+                            .insert(export_name, declaration);
+                    }
+                }
+                ExportResolution::Namespace(namespace_path) => {
+                    let namespace_exports = self.get_exports_of(&namespace_path)?;
+
+                    for (name, declaration) in namespace_exports.named_exports.iter() {
+                        module_exports
+                            .named_exports
+                            .insert(format!("{export_name}.{name}"), declaration.to_owned());
+                    }
+
+                    for (name, doc_tags) in namespace_exports.doc_tags.iter() {
+                        module_exports
+                            .doc_tags
+                            .insert(format!("{export_name}.{name}"), *doc_tags);
+                    }
+
+                    for (name, origin_paths) in namespace_exports.ambiguous_exports.iter() {
+                        module_exports
+                            .ambiguous_exports
+                            .insert(format!("{export_name}.{name}"), origin_paths.to_owned());
+                    }
+                }
+                ExportResolution::External(external_export) => {
+                    self.add_external_export(&mut module_exports, export_name, external_export)?;
+                }
+                // Names exported by several `export *` are not exported at all.
+                ExportResolution::Ambiguous(first_origin, second_origin) => {
+                    module_exports
+                        .ambiguous_exports
+                        .insert(export_name, (first_origin, second_origin));
+                }
+                ExportResolution::Missing => {}
+            }
+        }
+
+        Ok(module_exports)
+    }
+
+    /// Adds an export re-exported from another package, from that package's exports
+    /// when it can be resolved.
+    fn add_external_export(
+        &self,
+        module_exports: &mut ModuleExports,
+        export_name: String,
+        external_export: ExternalExport,
+    ) -> Result<()> {
+        let dependency_exports = match self.get_dependency_exports(&external_export.specifier)? {
+            Some(dependency_exports) => dependency_exports,
+            None => {
+                module_exports
+                    .external_exports
+                    .insert(export_name, external_export);
+
+                return Ok(());
+            }
+        };
+
+        let import_name = match &external_export.import_name {
+            ImportName::Named(import_name) => import_name,
+            ImportName::Namespace => {
+                for (name, declaration) in dependency_exports.named_exports.iter() {
+                    module_exports
+                        .named_exports
+                        .insert(format!("{export_name}.{name}"), declaration.to_owned());
+                }
+
+                return Ok(());
+            }
+        };
+
+        let declaration = if import_name.eq("default") {
+            dependency_exports.default_export.as_ref()
+        } else {
+            dependency_exports.named_exports.get(import_name)
+        };
+
+        if let Some(declaration) = declaration {
+            if export_name.eq("default") {
+                module_exports.default_export = Some(declaration.to_owned());
+            } else {
+                module_exports
+                    .named_exports
+                    .insert(export_name, declaration.to_owned());
+            }
+        } else if let Some(external_export) = dependency_exports.external_exports.get(import_name) {
+            // The dependency re-exports it from yet another package.
+            module_exports
+                .external_exports
+                .insert(export_name, external_export.to_owned());
+        }
+
+        Ok(())
+    }
+
+    /// Lists the names a module exports, per the ES spec's `GetExportedNames`. Names
+    /// from `export *` that turn out to be ambiguous are only excluded on resolution.
+    fn get_exported_names(
+        &self,
+        module_path: &Path,
+        star_set: &mut HashSet<PathBuf>,
+        star_stack: &mut Vec<PathBuf>,
+        external_star_exports: &mut BTreeSet<String>,
+    ) -> Result<Vec<String>> {
+        if star_set.contains(module_path) {
+            // Modules re-exported twice through different paths are not cycles.
+            if let Some(depth) = star_stack.iter().position(|path| path.eq(module_path)) {
+                self.contents
+                    .module_cache()
+                    .insert_cycle(&star_stack[depth..]);
+            }
+
+            return Ok(Vec::new());
+        }
+
+        star_set.insert(module_path.to_path_buf());
+
+        let linked_record = self.get_record_of(module_path)?;
+        let record = &linked_record.record;
+
+        let mut export_names: Vec<String> = record.local_exports.keys().cloned().collect();
+
+        export_names.extend(
+            record
+                .indirect_exports
+                .iter()
+                .map(|export| export.exported_name.to_owned()),
+        );
+
+        let mut known_names: HashSet<String> = export_names.iter().cloned().collect();
+
+        star_stack.push(module_path.to_path_buf());
+
+        for specifier in record.star_exports.iter() {
+            let star_names = match &linked_record.import_paths[specifier] {
+                Some(star_path) => self
+                    .get_exported_names(star_path, star_set, star_stack, external_star_exports)
+                    .with_context(|| {
+                        format!("Failed to get names exported by: {}", star_path.display())
+                    })?,
+                None => match self.get_dependency_exports(specifier)? {
+                    Some(dependency_exports) => {
+                        external_star_exports
+                            .extend(dependency_exports.external_star_exports.iter().cloned());
+
+                        dependency_exports
+                            .named_exports
+                            .keys()
+                            .chain(dependency_exports.external_exports.keys())
+                            .cloned()
+                            .collect()
+                    }
+                    None => {
+                        external_star_exports.insert(specifier.to_owned());
+                        continue;
+                    }
+                },
+            };
+
+            for star_name in star_names {
+                if star_name.ne("default") && known_names.insert(star_name.to_owned()) {
+                    export_names.push(star_name);
+                }
+            }
+        }
+
+        star_stack.pop();
+
+        Ok(export_names)
+    }
+
+    /// Finds the binding an export name of a module refers to, per the ES spec's
+    /// `ResolveExport`.
+    fn resolve_export(
+        &self,
+        module_path: &Path,
+        export_name: &str,
+        resolve_set: &mut HashSet<(PathBuf, String)>,
+        resolve_stack: &mut Vec<PathBuf>,
+    ) -> Result<ExportResolution> {
+        let resolve_key = (module_path.to_path_buf(), export_name.to_owned());
+
+        if resolve_set.contains(&resolve_key) {
+            if let Some(depth) = resolve_stack.iter().position(|path| path.eq(module_path)) {
+                self.contents
+                    .module_cache()
+                    .insert_cycle(&resolve_stack[depth..]);
+            }
+
+            return Ok(ExportResolution::Missing);
+        }
+
+        resolve_set.insert(resolve_key);
+
+        let linked_record = self.get_record_of(module_path)?;
+        let record = &linked_record.record;
+
+        if record.local_exports.contains_key(export_name) {
+            return Ok(ExportResolution::Binding {
+                module_path: module_path.to_path_buf(),
+                name: export_name.to_owned(),
+            });
+        }
+
+        let indirect_export = record
+            .indirect_exports
+            .iter()
+            .find(|export| export.exported_name.eq(export_name));
+
+        if let Some(indirect_export) = indirect_export {
+            let import_path = match &linked_record.import_paths[&indirect_export.specifier] {
+                Some(import_path) => import_path,
+                None => {
+                    return self.resolve_external_export(
+                        &indirect_export.specifier,
+                        indirect_export.import_name.to_owned(),
+                    )
+                }
+            };
+
+            let import_name = match &indirect_export.import_name {
+                ImportName::Named(import_name) => import_name,
+                ImportName::Namespace => {
+                    return Ok(ExportResolution::Namespace(import_path.to_owned()))
+                }
+            };
+
+            resolve_stack.push(module_path.to_path_buf());
+            let resolution =
+                self.resolve_export(import_path, import_name, resolve_set, resolve_stack);
+            resolve_stack.pop();
+
+            return resolution;
+        }
+
+        // A default export is never re-exported by `export *`.
+        if export_name.eq("default") {
+            return Ok(ExportResolution::Missing);
+        }
+
+        let mut star_resolution = ExportResolution::Missing;
+
+        resolve_stack.push(module_path.to_path_buf());
+
+        for specifier in record.star_exports.iter() {
+            let resolution = match &linked_record.import_paths[specifier] {
+                Some(star_path) => {
+                    self.resolve_export(star_path, export_name, resolve_set, resolve_stack)?
+                }
+                // The names of unresolved packages are unknown, so they cannot provide any.
+                None if self.get_dependency_exports(specifier)?.is_none() => continue,
+                None => self.resolve_external_export(
+                    specifier,
+                    ImportName::Named(export_name.to_owned()),
+                )?,
+            };
+
+            match resolution {
+                ExportResolution::Missing => continue,
+                ExportResolution::Ambiguous(..) => {
+                    star_resolution = resolution;
+                    break;
+                }
+                resolution if star_resolution.eq(&ExportResolution::Missing) => {
+                    star_resolution = resolution;
+                }
+                resolution if star_resolution.ne(&resolution) => {
+                    // Both resolutions point to a module, as neither is missing nor
+                    // ambiguous.
+                    star_resolution = ExportResolution::Ambiguous(
+                        star_resolution.origin().unwrap(),
+                        resolution.origin().unwrap(),
+                    );
+                    break;
+                }
+                _ => continue,
+            }
+        }
+
+        resolve_stack.pop();
+
+        Ok(star_resolution)
+    }
+
+    /// Resolves an export of another package, which is missing only when that package is
+    /// resolved and does not export it.
+    fn resolve_external_export(
+        &self,
+        specifier: &str,
+        import_name: ImportName,
+    ) -> Result<ExportResolution> {
+        if let (Some(dependency_exports), ImportName::Named(import_name)) =
+            (self.get_dependency_exports(specifier)?, &import_name)
+        {
+            let is_exported = if import_name.eq("default") {
+                dependency_exports.default_export.is_some()
+            } else {
+                dependency_exports.named_exports.contains_key(import_name)
+                    || dependency_exports
+                        .external_exports
+                        .contains_key(import_name)
+            };
+
+            if !is_exported {
+                return Ok(ExportResolution::Missing);
+            }
+        }
+
+        Ok(ExportResolution::External(ExternalExport {
+            package_name: get_package_name(specifier).into(),
+            specifier: specifier.into(),
+            import_name,
+        }))
+    }
+
+    /// Returns the exports of a module of another package, when imports of other
+    /// packages are resolved and it is installed.
+    fn get_dependency_exports(&self, specifier: &str) -> Result<Option<Arc<ModuleExports>>> {
+        if !self.resolve_node_modules {
+            return Ok(None);
+        }
+
+        let package_name = get_package_name(specifier);
+
+        let dependency_entries = match self.get_dependency_entries(package_name) {
+            Some(dependency_entries) => dependency_entries,
+            None => return Ok(None),
+        };
+
+        let subpath = format!(".{}", &specifier[package_name.len()..]);
+
+        // A subpath the package does not export is reported like any unresolved import.
+        let entry = match find_dependency_entry(&dependency_entries, &subpath) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let dependency_exports = ModuleGraph::new(&entry.contents, false)
+            .get_exports_of(&entry.path)
+            .with_context(|| format!("Failed to get exports of installed package: {specifier}"))?;
+
+        Ok(Some(dependency_exports))
+    }
+
+    fn get_dependency_entries(&self, package_name: &str) -> Option<Arc<PkgEntries>> {
+        let module_cache = self.contents.module_cache();
+
+        if let Some(dependency_entries) =
+            module_cache.dependencies.lock().unwrap().get(package_name)
+        {
+            return dependency_entries.to_owned();
+        }
+
+        // Installed packages whose entries cannot be loaded, e.g. as they only declare some
+        // of them, are left unresolved like packages that are not installed.
+        let dependency_entries = find_installed_pkg_dir(&self.contents.pkg_dir, package_name)
+            .and_then(|dependency_dir| load_entries_from_dir(dependency_dir).ok())
+            .map(Arc::new);
+
+        module_cache
+            .dependencies
+            .lock()
+            .unwrap()
+            .insert(package_name.into(), dependency_entries.to_owned());
+
+        dependency_entries
+    }
+
+    fn get_record_of(&self, module_path: &Path) -> Result<Arc<LinkedRecord>> {
+        let module_cache = self.contents.module_cache();
+
+        if let Some(linked_record) = module_cache.records.lock().unwrap().get(module_path) {
+            return Ok(Arc::clone(linked_record));
+        }
+
+        let (module, comments) = parse_module(self.contents, module_path)?;
+
+        let record = get_module_record(module_path, &module, &comments).with_context(|| {
+            format!("Failed to get exports in module: {}", module_path.display())
+        })?;
+
+        let mut import_paths = HashMap::new();
+
+        let specifiers = record
+            .indirect_exports
+            .iter()
+            .map(|export| &export.specifier)
+            .chain(record.star_exports.iter());
+
+        for specifier in specifiers {
+            if !import_paths.contains_key(specifier) {
+                let import_path = self.resolve_import(module_path, specifier)?;
+                import_paths.insert(specifier.to_owned(), import_path);
+            }
+        }
+
+        let linked_record = Arc::new(LinkedRecord {
+            record,
+            import_paths,
+        });
+
+        module_cache
+            .records
+            .lock()
+            .unwrap()
+            .insert(module_path.to_path_buf(), Arc::clone(&linked_record));
+
+        Ok(linked_record)
+    }
+}
+
+/// Returns the name of the package a bare specifier imports from, e.g. "@scope/pkg" for
+/// "@scope/pkg/utils".
+pub fn get_package_name(specifier: &str) -> &str {
+    let name_segment_count = if specifier.starts_with('@') { 2 } else { 1 };
+
+    match specifier.match_indices('/').nth(name_segment_count - 1) {
+        Some((separator_index, _)) => &specifier[..separator_index],
+        None => specifier,
+    }
+}
+
+/// Finds an installed package the way Node does, from the `node_modules` directory of
+/// the package or of any of its parent directories.
+fn find_installed_pkg_dir(pkg_dir: &Path, package_name: &str) -> Option<PathBuf> {
+    let pkg_dir = pkg_dir.canonicalize().unwrap_or(pkg_dir.to_path_buf());
+
+    pkg_dir
+        .ancestors()
+        .map(|dir| dir.join("node_modules").join(package_name))
+        .find(|installed_dir| installed_dir.join("package.json").is_file())
+}
+
+/// Picks the entry of an installed package that an import of one of its subpaths loads,
+/// preferring ES module builds.
+fn find_dependency_entry<'entries>(
+    entries: &'entries PkgEntries,
+    subpath: &str,
+) -> Option<&'entries PkgEntry> {
+    let mut subpath_entries: Vec<&PkgEntry> = entries
+        .exports
+        .values()
+        .filter(|entry| entry.subpath.as_deref().eq(&Some(subpath)))
+        .collect();
+
+    subpath_entries.sort_by_key(|entry| (!entry.name.contains("import"), entry.name.to_owned()));
+
+    if let Some(entry) = subpath_entries.first() {
+        return Some(entry);
+    }
+
+    // Deep imports into packages without an exports field are not resolved.
+    if !entries.exports.is_empty() || subpath.ne(".") {
+        return None;
+    }
+
+    entries
+        .module
+        .values()
+        .next()
+        .or(entries.main.values().next())
+}
+
+fn get_import_candidates(import_path: &Path) -> Vec<PathBuf> {
+    let mut candidates = vec![import_path.to_path_buf()];
+
+    if let Some(extension) = import_path.extension().and_then(|ext| ext.to_str()) {
+        for (output_extension, source_extensions) in TS_SOURCE_EXTENSIONS {
+            if extension.eq(output_extension) {
+                for source_extension in source_extensions {
+                    candidates.push(import_path.with_extension(source_extension));
+                }
+            }
+        }
+    }
+
+    let import_path = import_path.as_os_str().to_string_lossy();
+
+    for extension in IMPORT_EXTENSIONS {
+        candidates.push(PathBuf::from(format!("{import_path}.{extension}")));
+    }
+
+    for extension in IMPORT_EXTENSIONS {
+        candidates.push(PathBuf::from(format!("{import_path}/index.{extension}")));
+    }
+
+    candidates
+}
+
+/// Declaration files resolve imports to other declaration files first, and to TypeScript
+/// sources otherwise.
+fn get_declaration_import_candidates(import_path: &Path) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    let extension = import_path.extension().and_then(|ext| ext.to_str());
+
+    for (output_extension, declaration_extension) in DECLARATION_EXTENSIONS {
+        if extension.eq(&Some(output_extension)) {
+            candidates.push(import_path.with_extension(declaration_extension));
+        }
+    }
+
+    let import_path_name = import_path.as_os_str().to_string_lossy();
+
+    candidates.push(PathBuf::from(format!("{import_path_name}.d.ts")));
+    candidates.push(PathBuf::from(format!("{import_path_name}/index.d.ts")));
+    candidates.extend(get_import_candidates(import_path));
+
+    candidates
+}
+
+pub fn is_declaration_file(path: &Path) -> bool {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    [".d.ts", ".d.mts", ".d.cts"]
+        .iter()
+        .any(|extension| file_name.ends_with(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Creates a package of the given modules in a temporary directory, which the caller
+    /// removes once done.
+    fn create_pkg_contents(fixture_name: &str, modules: &[(&str, &str)]) -> PkgContents {
+        let pkg_dir = std::env::temp_dir().join(format!(
+            "breakpoint-graph-{fixture_name}-{}",
+            std::process::id()
+        ));
+
+        let _ = fs::remove_dir_all(&pkg_dir);
+        fs::create_dir_all(&pkg_dir).unwrap();
+        fs::write(pkg_dir.join("package.json"), "{}").unwrap();
+
+        for (module_path, source) in modules {
+            fs::write(pkg_dir.join(module_path), source).unwrap();
+        }
+
+        PkgContents::new(pkg_dir, &json::object! {}, None).unwrap()
+    }
+
+    fn get_named_exports(module_exports: &ModuleExports) -> Vec<&str> {
+        let mut names: Vec<&str> = module_exports
+            .named_exports
+            .keys()
+            .map(String::as_str)
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn cycles_start_from_their_first_module_in_path_order() {
+        let module_cache = ModuleCache::default();
+
+        module_cache.insert_cycle(&[PathBuf::from("c.js"), "a.js".into(), "b.js".into()]);
+        module_cache.insert_cycle(&[PathBuf::from("b.js"), "c.js".into(), "a.js".into()]);
+
+        assert_eq!(
+            module_cache.get_cycles(),
+            [[PathBuf::from("a.js"), "b.js".into(), "c.js".into()]]
+        );
+    }
+
+    #[test]
+    fn star_export_cycles_are_reported_and_still_linked() {
+        let contents = create_pkg_contents(
+            "star-cycle",
+            &[
+                ("a.js", "export * from './b.js'; export const a = 1;"),
+                ("b.js", "export * from './a.js'; export const b = 1;"),
+            ],
+        );
+
+        let module_exports = ModuleGraph::new(&contents, false)
+            .get_exports_of(Path::new("a.js"))
+            .unwrap();
+
+        fs::remove_dir_all(&contents.pkg_dir).unwrap();
+
+        assert_eq!(get_named_exports(&module_exports), ["a", "b"]);
+        assert_eq!(
+            contents.module_cache().get_cycles(),
+            [[PathBuf::from("a.js"), "b.js".into()]]
+        );
+    }
+
+    #[test]
+    fn namespace_export_cycles_are_reported() {
+        let contents = create_pkg_contents(
+            "namespace-cycle",
+            &[
+                ("a.js", "export * as b from './b.js'; export const a = 1;"),
+                ("b.js", "export * as a from './a.js'; export const b = 1;"),
+            ],
+        );
+
+        let module_exports = ModuleGraph::new(&contents, false)
+            .get_exports_of(Path::new("a.js"))
+            .unwrap();
+
+        fs::remove_dir_all(&contents.pkg_dir).unwrap();
+
+        assert_eq!(get_named_exports(&module_exports), ["a", "b.b"]);
+        assert_eq!(
+            contents.module_cache().get_cycles(),
+            [[PathBuf::from("a.js"), "b.js".into()]]
+        );
+    }
+
+    #[test]
+    fn names_of_several_star_exports_are_ambiguous() {
+        let contents = create_pkg_contents(
+            "ambiguous",
+            &[
+                (
+                    "index.js",
+                    "export * from './x.js'; export * from './y.js';",
+                ),
+                (
+                    "x.js",
+                    "export const dup = 1; export const onlyX = 1; export * from './z.js';",
+                ),
+                (
+                    "y.js",
+                    "export const dup = 2; export { shared } from './z.js';",
+                ),
+                ("z.js", "export const shared = 1;"),
+            ],
+        );
+
+        let module_exports = ModuleGraph::new(&contents, false)
+            .get_exports_of(Path::new("index.js"))
+            .unwrap();
+
+        fs::remove_dir_all(&contents.pkg_dir).unwrap();
+
+        // The same binding reached through both modules is not ambiguous.
+        assert_eq!(get_named_exports(&module_exports), ["onlyX", "shared"]);
+        assert_eq!(
+            module_exports.ambiguous_exports.get("dup"),
+            Some(&(String::from("x.js"), String::from("y.js")))
+        );
+        assert!(contents.module_cache().get_cycles().is_empty());
+    }
+}
@@ -1,3 +1,8 @@
+pub mod docs;
 pub mod entity;
+pub mod graph;
 pub mod parser;
+pub mod positions;
+pub mod types;
+pub mod value;
 pub mod walker;
@@ -1,8 +1,9 @@
 use crate::fs::file::FileExt;
-use crate::pkg::entries::PkgEntry;
+use crate::pkg::contents::PkgContents;
 use anyhow::{bail, Context, Result};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use swc_common::comments::SingleThreadedComments;
 use swc_common::errors::{ColorConfig, Handler};
 use swc_common::input::StringInput;
 use swc_common::sync::Lrc;
@@ -11,36 +12,43 @@ use swc_ecma_ast::{EsVersion, Module};
 use swc_ecma_parser::lexer::Lexer;
 use swc_ecma_parser::{Parser, Syntax};
 
-pub fn parse_pkg_entry(entry: &PkgEntry) -> Result<Module> {
-    let entry_path = &entry.path;
-
-    // Entry module files are loaded from a PkgEntry's load_file method instead of the
-    // SourceMap equivalent since we load a file either from disk or from a tarball.
-    let file_data = match entry.load_file()? {
+/// Parses a module, along with its comments, which hold the doc tags of its declarations.
+pub fn parse_module(
+    contents: &PkgContents,
+    module_path: &Path,
+) -> Result<(Module, SingleThreadedComments)> {
+    // Modules are loaded through the package contents instead of the SourceMap, since
+    // they are read either from disk or from a tarball.
+    let file_data = match contents.load_file(&module_path.to_path_buf())? {
         Some(data) => String::from_utf8(data)?,
-        None => bail!("Entry module '{}' does not exist.", entry_path.display()),
+        None => bail!("Module '{}' does not exist.", module_path.display()),
     };
 
+    parse_module_source(module_path, file_data)
+}
+
+/// Parses the source of a module, with the syntax its path's extension implies.
+pub fn parse_module_source(
+    module_path: &Path,
+    file_data: String,
+) -> Result<(Module, SingleThreadedComments)> {
     let source_map: Lrc<SourceMap> = Default::default();
-    let source_name = FileName::Real(entry_path.to_owned());
+    let source_name = FileName::Real(module_path.to_owned());
     let source_file = source_map.new_source_file(source_name, file_data);
 
-    parse_source_file(source_map, Rc::clone(&source_file)).with_context(|| {
-        format!(
-            "Failed to parse package entry module: {}",
-            entry_path.display()
-        )
-    })
-}
+    let comments = SingleThreadedComments::default();
 
-pub fn parse_import(file_path: &Path) -> Result<Module> {
-    let source_map: Lrc<SourceMap> = Default::default();
-    let source_file = source_map.load_file(file_path)?;
+    let module = parse_source_file(source_map, Rc::clone(&source_file), &comments)
+        .with_context(|| format!("Failed to parse module: {}", module_path.display()))?;
 
-    parse_source_file(source_map, Rc::clone(&source_file))
+    Ok((module, comments))
 }
 
-fn parse_source_file(source_map: Lrc<SourceMap>, source_file: Rc<SourceFile>) -> Result<Module> {
+fn parse_source_file(
+    source_map: Lrc<SourceMap>,
+    source_file: Rc<SourceFile>,
+    comments: &SingleThreadedComments,
+) -> Result<Module> {
     let source_file_path = PathBuf::from(source_file.name.to_string());
     let source_file_ext = FileExt::from(&source_file_path);
 
@@ -54,7 +62,7 @@ fn parse_source_file(source_map: Lrc<SourceMap>, source_file: Rc<SourceFile>) ->
         syntax,
         EsVersion::EsNext,
         StringInput::from(&*source_file),
-        None,
+        Some(comments),
     );
 
     let mut parser = Parser::new_from(lexer);
@@ -0,0 +1,295 @@
+use crate::ecma::entity::EntityDeclaration;
+use crate::ecma::types::{
+    get_function_type, get_pat_param_type, get_type_ann_repr, TypeDeclaration, TypeRepr,
+};
+use std::collections::HashMap;
+use swc_ecma_ast::{
+    Accessibility, Class, ClassMember, MethodKind, ParamOrTsParamProp, Pat, TsParamPropParam,
+};
+
+/// Where values of a type flow between a package and its consumers: into the package, e.g.
+/// as parameters, out of it, e.g. as return values, or both ways.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct TypePosition {
+    pub is_input: bool,
+    pub is_output: bool,
+}
+
+impl TypePosition {
+    pub const INPUT: Self = Self {
+        is_input: true,
+        is_output: false,
+    };
+
+    pub const OUTPUT: Self = Self {
+        is_input: false,
+        is_output: true,
+    };
+
+    pub const BOTH: Self = Self {
+        is_input: true,
+        is_output: true,
+    };
+
+    /// The position of a function's parameters, given the position of the function itself.
+    fn flipped(self) -> Self {
+        Self {
+            is_input: self.is_output,
+            is_output: self.is_input,
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            is_input: self.is_input || other.is_input,
+            is_output: self.is_output || other.is_output,
+        }
+    }
+}
+
+/// The positions exported functions, classes & variables use types in, by type name. Types
+/// only used by other types get the positions of those. Types are matched by the name they
+/// are referenced with, so imported types that are renamed are not found.
+pub fn get_type_positions<'module>(
+    declarations: impl Iterator<Item = &'module EntityDeclaration>,
+) -> HashMap<String, TypePosition> {
+    let mut type_declarations: HashMap<String, TypeDeclaration> = HashMap::new();
+    let mut usages: Vec<(TypeRepr, TypePosition)> = Vec::new();
+
+    for declaration in declarations {
+        match declaration {
+            // Consumers receive exported functions, so they call them with inputs and
+            // get outputs back.
+            EntityDeclaration::Func(..) | EntityDeclaration::Overloads(..) => {
+                for signature in declaration.signatures() {
+                    usages.push((
+                        TypeRepr::Function(Box::new(signature)),
+                        TypePosition::OUTPUT,
+                    ));
+                }
+            }
+            EntityDeclaration::Class(_, class) => add_class_usages(class, &mut usages),
+            EntityDeclaration::Var(_, declarator) => {
+                if let Pat::Ident(binding) = &declarator.name {
+                    let type_repr = get_type_ann_repr(binding.type_ann.as_deref());
+
+                    usages.push((type_repr, TypePosition::OUTPUT));
+                }
+            }
+            _ => {
+                if let Some(type_declaration) = declaration.type_declaration() {
+                    type_declarations.insert(declaration.name().to_owned(), type_declaration);
+                }
+            }
+        }
+    }
+
+    let mut pending_references = Vec::new();
+
+    for (type_repr, position) in usages {
+        add_type_references(&type_repr, position, &mut pending_references);
+    }
+
+    let mut positions: HashMap<String, TypePosition> = HashMap::new();
+
+    while let Some((name, position)) = pending_references.pop() {
+        let known_position = positions.entry(name.to_owned()).or_default();
+        let merged_position = known_position.union(position);
+
+        if merged_position.eq(known_position) {
+            continue;
+        }
+
+        *known_position = merged_position;
+
+        if let Some(type_declaration) = type_declarations.get(&name) {
+            for base_type in type_declaration.extends.iter() {
+                add_type_references(base_type, merged_position, &mut pending_references);
+            }
+
+            add_type_references(
+                &type_declaration.shape,
+                merged_position,
+                &mut pending_references,
+            );
+        }
+    }
+
+    positions
+}
+
+fn add_class_usages(class: &Class, usages: &mut Vec<(TypeRepr, TypePosition)>) {
+    let is_public =
+        |accessibility: &Option<Accessibility>| accessibility.ne(&Some(Accessibility::Private));
+
+    for member in class.body.iter() {
+        match member {
+            ClassMember::Constructor(constructor) if is_public(&constructor.accessibility) => {
+                for param in constructor.params.iter() {
+                    match param {
+                        ParamOrTsParamProp::Param(param) => {
+                            let type_repr = get_pat_param_type(&param.pat).type_repr;
+
+                            usages.push((type_repr, TypePosition::INPUT));
+                        }
+                        // Parameter properties are readable from instances as well.
+                        ParamOrTsParamProp::TsParamProp(property) => {
+                            let pattern = match &property.param {
+                                TsParamPropParam::Ident(ident) => Pat::Ident(ident.to_owned()),
+                                TsParamPropParam::Assign(assign) => Pat::Assign(assign.to_owned()),
+                            };
+
+                            let position = if is_public(&property.accessibility) {
+                                TypePosition::BOTH
+                            } else {
+                                TypePosition::INPUT
+                            };
+
+                            usages.push((get_pat_param_type(&pattern).type_repr, position));
+                        }
+                    }
+                }
+            }
+            ClassMember::Method(method) if is_public(&method.accessibility) => {
+                let function = get_function_type(&method.function);
+
+                match method.kind {
+                    MethodKind::Method => {
+                        usages.push((TypeRepr::Function(Box::new(function)), TypePosition::OUTPUT))
+                    }
+                    MethodKind::Getter => usages.push((function.returns, TypePosition::OUTPUT)),
+                    MethodKind::Setter => {
+                        for param in function.params {
+                            usages.push((param.type_repr, TypePosition::INPUT));
+                        }
+                    }
+                }
+            }
+            ClassMember::ClassProp(property) if is_public(&property.accessibility) => {
+                let position = if property.readonly {
+                    TypePosition::OUTPUT
+                } else {
+                    TypePosition::BOTH
+                };
+
+                usages.push((get_type_ann_repr(property.type_ann.as_deref()), position));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn add_type_references(
+    type_repr: &TypeRepr,
+    position: TypePosition,
+    references: &mut Vec<(String, TypePosition)>,
+) {
+    match type_repr {
+        TypeRepr::Reference { name, type_args } => {
+            references.push((name.to_owned(), position));
+
+            for type_arg in type_args {
+                add_type_references(type_arg, position, references);
+            }
+        }
+        TypeRepr::Array(element) => add_type_references(element, position, references),
+        TypeRepr::Tuple(members) | TypeRepr::Union(members) | TypeRepr::Intersection(members) => {
+            for member in members {
+                add_type_references(member, position, references);
+            }
+        }
+        TypeRepr::Function(function) => {
+            for param in function.params.iter() {
+                add_type_references(&param.type_repr, position.flipped(), references);
+            }
+
+            add_type_references(&function.returns, position, references);
+        }
+        TypeRepr::Object(object) => {
+            for property in object.properties.values() {
+                add_type_references(&property.type_repr, position, references);
+            }
+
+            for value in object.index_signatures.values() {
+                add_type_references(value, position, references);
+            }
+
+            for call_signature in object.call_signatures.iter() {
+                add_type_references(
+                    &TypeRepr::Function(Box::new(call_signature.to_owned())),
+                    position,
+                    references,
+                );
+            }
+        }
+        TypeRepr::Keyword(_) | TypeRepr::Literal(_) | TypeRepr::Opaque(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecma::parser::parse_module_source;
+    use crate::ecma::walker::get_module_record;
+    use std::path::Path;
+
+    fn get_positions(source: &str) -> HashMap<String, TypePosition> {
+        let module_path = Path::new("index.ts");
+        let (module, comments) = parse_module_source(module_path, source.into()).unwrap();
+        let module_record = get_module_record(module_path, &module, &comments).unwrap();
+
+        get_type_positions(module_record.local_exports.values())
+    }
+
+    #[test]
+    fn function_params_are_inputs_and_returns_outputs() {
+        let positions = get_positions(
+            r#"
+            export interface Options { retries: number }
+            export interface Result { ok: boolean }
+            export interface Both { id: string }
+            export function run(options: Options, both: Both): Result;
+            export function echo(both: Both): Both;
+            "#,
+        );
+
+        assert!(positions["Options"].eq(&TypePosition::INPUT));
+        assert!(positions["Result"].eq(&TypePosition::OUTPUT));
+        assert!(positions["Both"].eq(&TypePosition::BOTH));
+    }
+
+    #[test]
+    fn callback_params_flip_the_position() {
+        let positions = get_positions(
+            r#"
+            export interface Event { name: string }
+            export interface Options { onEvent: (event: Event) => void }
+            export function listen(options: Options): void;
+            "#,
+        );
+
+        assert!(positions["Options"].eq(&TypePosition::INPUT));
+        assert!(positions["Event"].eq(&TypePosition::OUTPUT));
+    }
+
+    #[test]
+    fn class_members_use_types_by_access() {
+        let positions = get_positions(
+            r#"
+            export class Client {
+                constructor(config: Config) {}
+                readonly status: Status;
+                state: State;
+                set timeout(timeout: Timeout) {}
+                private secret: Secret;
+            }
+            "#,
+        );
+
+        assert!(positions["Config"].eq(&TypePosition::INPUT));
+        assert!(positions["Status"].eq(&TypePosition::OUTPUT));
+        assert!(positions["State"].eq(&TypePosition::BOTH));
+        assert!(positions["Timeout"].eq(&TypePosition::INPUT));
+        assert!(!positions.contains_key("Secret"));
+    }
+}
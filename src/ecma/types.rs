@@ -0,0 +1,798 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use swc_common::EqIgnoreSpan;
+use swc_ecma_ast::{
+    Expr, Function, Lit, Pat, TsEntityName, TsFnOrConstructorType, TsFnParam, TsInterfaceDecl,
+    TsKeywordTypeKind, TsLit, TsType, TsTypeAliasDecl, TsTypeAnn, TsTypeElement, TsTypeParamDecl,
+    TsUnionOrIntersectionType,
+};
+
+/// A structural model of a TypeScript type, precise enough to compare declarations across
+/// versions without a type checker. Types it doesn't model are only compared for equality.
+#[derive(Clone, PartialEq)]
+pub enum TypeRepr {
+    Keyword(&'static str),
+    /// A literal type, as written in the source.
+    Literal(String),
+    Reference {
+        name: String,
+        type_args: Vec<TypeRepr>,
+    },
+    Array(Box<TypeRepr>),
+    Tuple(Vec<TypeRepr>),
+    Union(Vec<TypeRepr>),
+    Intersection(Vec<TypeRepr>),
+    Function(Box<FunctionType>),
+    Object(Box<ObjectType>),
+    Opaque(OpaqueType),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct FunctionType {
+    pub params: Vec<ParamType>,
+    pub returns: TypeRepr,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ParamType {
+    pub type_repr: TypeRepr,
+    pub is_optional: bool,
+    pub is_rest: bool,
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct ObjectType {
+    pub properties: BTreeMap<String, PropertyType>,
+    /// Value types by key type, e.g. "string" for `[key: string]: T`.
+    pub index_signatures: BTreeMap<String, TypeRepr>,
+    pub call_signatures: Vec<FunctionType>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct PropertyType {
+    pub type_repr: TypeRepr,
+    pub is_optional: bool,
+    pub is_readonly: bool,
+}
+
+#[derive(Clone)]
+pub struct OpaqueType(Box<TsType>);
+
+impl PartialEq for OpaqueType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_span(&other.0)
+    }
+}
+
+/// An interface or type alias declaration.
+pub struct TypeDeclaration {
+    pub type_params: Vec<TypeParam>,
+    /// The types an interface extends.
+    pub extends: Vec<TypeRepr>,
+    pub shape: TypeRepr,
+}
+
+pub struct TypeParam {
+    pub name: String,
+    pub has_default: bool,
+}
+
+impl TypeRepr {
+    /// Whether every value of this type is also a value of the target type. Unknown cases
+    /// are considered not assignable, unless the types are equal.
+    pub fn is_assignable_to(&self, target: &TypeRepr) -> bool {
+        if self.eq(target) {
+            return true;
+        }
+
+        match (self, target) {
+            (_, TypeRepr::Keyword("any" | "unknown")) => true,
+            (TypeRepr::Keyword("any" | "never"), _) => true,
+            (TypeRepr::Union(sources), _) => {
+                sources.iter().all(|source| source.is_assignable_to(target))
+            }
+            (TypeRepr::Keyword("boolean"), TypeRepr::Union(targets)) => {
+                let true_type = TypeRepr::Literal("true".into());
+                let false_type = TypeRepr::Literal("false".into());
+
+                (targets.contains(&true_type) && targets.contains(&false_type))
+                    || targets.iter().any(|target| self.is_assignable_to(target))
+            }
+            (_, TypeRepr::Union(targets)) => {
+                targets.iter().any(|target| self.is_assignable_to(target))
+            }
+            (_, TypeRepr::Intersection(targets)) => {
+                targets.iter().all(|target| self.is_assignable_to(target))
+            }
+            (TypeRepr::Intersection(sources), _) => {
+                sources.iter().any(|source| source.is_assignable_to(target))
+            }
+            (TypeRepr::Literal(literal), TypeRepr::Keyword(keyword)) => {
+                get_literal_keyword(literal).eq(*keyword)
+            }
+            (TypeRepr::Keyword("undefined"), TypeRepr::Keyword("void")) => true,
+            (TypeRepr::Array(source), TypeRepr::Array(target)) => source.is_assignable_to(target),
+            (TypeRepr::Tuple(sources), TypeRepr::Array(target)) => {
+                sources.iter().all(|source| source.is_assignable_to(target))
+            }
+            (TypeRepr::Tuple(sources), TypeRepr::Tuple(targets)) => {
+                sources.len().eq(&targets.len())
+                    && sources
+                        .iter()
+                        .zip(targets)
+                        .all(|(source, target)| source.is_assignable_to(target))
+            }
+            (
+                TypeRepr::Reference {
+                    name: source_name,
+                    type_args: source_args,
+                },
+                TypeRepr::Reference {
+                    name: target_name,
+                    type_args: target_args,
+                },
+            ) => {
+                source_name.eq(target_name)
+                    && source_args.len().eq(&target_args.len())
+                    && source_args
+                        .iter()
+                        .zip(target_args)
+                        .all(|(source, target)| source.is_assignable_to(target))
+            }
+            (TypeRepr::Object(source), TypeRepr::Object(target)) => source.is_assignable_to(target),
+            (TypeRepr::Function(source), TypeRepr::Function(target)) => {
+                source.is_assignable_to(target)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl ObjectType {
+    fn is_assignable_to(&self, target: &ObjectType) -> bool {
+        let are_properties_assignable = target.properties.iter().all(|(name, target_property)| {
+            match self.properties.get(name) {
+                Some(property) => {
+                    (!property.is_optional || target_property.is_optional)
+                        && property
+                            .type_repr
+                            .is_assignable_to(&target_property.type_repr)
+                }
+                None => target_property.is_optional,
+            }
+        });
+
+        let are_index_signatures_assignable =
+            target.index_signatures.iter().all(|(key, target_value)| {
+                match self.index_signatures.get(key) {
+                    Some(value) => value.is_assignable_to(target_value),
+                    None => false,
+                }
+            });
+
+        let are_call_signatures_assignable = target.call_signatures.iter().all(|target_call| {
+            self.call_signatures
+                .iter()
+                .any(|call| call.is_assignable_to(target_call))
+        });
+
+        are_properties_assignable
+            && are_index_signatures_assignable
+            && are_call_signatures_assignable
+    }
+}
+
+impl FunctionType {
+    pub fn is_assignable_to(&self, target: &FunctionType) -> bool {
+        let param_count = self.params.len().max(target.params.len());
+
+        // Parameters are contravariant, so the target's must be accepted by the source's.
+        // Arguments the target may omit must be optional for the source as well.
+        let are_params_assignable =
+            (0..param_count).all(|index| match (self.get_arg(index), target.get_arg(index)) {
+                (Some((param_type, is_optional)), Some((target_type, is_target_optional))) => {
+                    (is_optional || !is_target_optional)
+                        && target_type.is_assignable_to(&param_type)
+                }
+                (Some((_, is_optional)), None) => is_optional,
+                (None, _) => true,
+            });
+
+        let are_returns_assignable = target.returns.eq(&TypeRepr::Keyword("void"))
+            || self.returns.is_assignable_to(&target.returns);
+
+        are_params_assignable && are_returns_assignable
+    }
+
+    /// The type of the argument at the given position, and whether it may be omitted. A
+    /// rest parameter accepts every argument from its own position onwards.
+    fn get_arg(&self, index: usize) -> Option<(TypeRepr, bool)> {
+        let rest_index = self
+            .params
+            .iter()
+            .position(|param| param.is_rest)
+            .unwrap_or(self.params.len());
+
+        if index.lt(&rest_index) {
+            let param = &self.params[index];
+
+            return Some((param.type_repr.to_owned(), param.is_optional));
+        }
+
+        let rest_type = match &self.params.get(rest_index)?.type_repr {
+            TypeRepr::Array(element_type) => element_type.as_ref().to_owned(),
+            TypeRepr::Reference { name, type_args }
+                if matches!(name.as_str(), "Array" | "ReadonlyArray") && type_args.len().eq(&1) =>
+            {
+                type_args[0].to_owned()
+            }
+            TypeRepr::Tuple(element_types) => {
+                return element_types
+                    .get(index - rest_index)
+                    .map(|element_type| (element_type.to_owned(), false));
+            }
+            _ => TypeRepr::Keyword("any"),
+        };
+
+        Some((rest_type, true))
+    }
+}
+
+impl TypeDeclaration {
+    pub fn from_interface(interface: &TsInterfaceDecl) -> Self {
+        let extends = interface
+            .extends
+            .iter()
+            .map(|base| TypeRepr::Reference {
+                name: get_expression_name(&base.expr),
+                type_args: base
+                    .type_args
+                    .as_ref()
+                    .map(|type_args| {
+                        type_args
+                            .params
+                            .iter()
+                            .map(|arg| get_type_repr(arg))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        Self {
+            type_params: get_type_params(interface.type_params.as_deref()),
+            extends,
+            shape: TypeRepr::Object(Box::new(get_object_type(&interface.body.body))),
+        }
+    }
+
+    pub fn from_type_alias(type_alias: &TsTypeAliasDecl) -> Self {
+        Self {
+            type_params: get_type_params(type_alias.type_params.as_deref()),
+            extends: Vec::new(),
+            shape: get_type_repr(&type_alias.type_ann),
+        }
+    }
+}
+
+pub fn get_type_repr(ts_type: &TsType) -> TypeRepr {
+    match ts_type {
+        TsType::TsKeywordType(keyword) => TypeRepr::Keyword(get_keyword_name(&keyword.kind)),
+        TsType::TsThisType(_) => TypeRepr::Reference {
+            name: "this".into(),
+            type_args: Vec::new(),
+        },
+        TsType::TsParenthesizedType(parenthesized) => get_type_repr(&parenthesized.type_ann),
+        TsType::TsOptionalType(optional) => get_type_repr(&optional.type_ann),
+        TsType::TsTypeRef(reference) => TypeRepr::Reference {
+            name: get_entity_name(&reference.type_name),
+            type_args: reference
+                .type_params
+                .as_ref()
+                .map(|type_args| {
+                    type_args
+                        .params
+                        .iter()
+                        .map(|arg| get_type_repr(arg))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        },
+        TsType::TsArrayType(array) => TypeRepr::Array(Box::new(get_type_repr(&array.elem_type))),
+        TsType::TsTupleType(tuple) => TypeRepr::Tuple(
+            tuple
+                .elem_types
+                .iter()
+                .map(|element| get_type_repr(&element.ty))
+                .collect(),
+        ),
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(union)) => {
+            get_union_repr(
+                union
+                    .types
+                    .iter()
+                    .map(|member| get_type_repr(member))
+                    .collect(),
+            )
+        }
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
+            intersection,
+        )) => TypeRepr::Intersection(
+            intersection
+                .types
+                .iter()
+                .map(|member| get_type_repr(member))
+                .collect(),
+        ),
+        TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(function)) => {
+            TypeRepr::Function(Box::new(FunctionType {
+                params: function.params.iter().map(get_fn_param_type).collect(),
+                returns: get_type_ann_repr(Some(&function.type_ann)),
+            }))
+        }
+        TsType::TsTypeLit(literal) => TypeRepr::Object(Box::new(get_object_type(&literal.members))),
+        TsType::TsLitType(literal) => match &literal.lit {
+            TsLit::Number(number) => TypeRepr::Literal(number.value.to_string()),
+            TsLit::Str(string) => TypeRepr::Literal(json::stringify(string.value.as_ref())),
+            TsLit::Bool(boolean) => TypeRepr::Literal(boolean.value.to_string()),
+            TsLit::BigInt(big_int) => TypeRepr::Literal(format!("{}n", big_int.value)),
+            TsLit::Tpl(_) => TypeRepr::Opaque(OpaqueType(Box::new(ts_type.to_owned()))),
+        },
+        _ => TypeRepr::Opaque(OpaqueType(Box::new(ts_type.to_owned()))),
+    }
+}
+
+/// The type of an annotation, which is `any` when omitted.
+pub fn get_type_ann_repr(type_ann: Option<&TsTypeAnn>) -> TypeRepr {
+    type_ann
+        .map(|type_ann| get_type_repr(&type_ann.type_ann))
+        .unwrap_or(TypeRepr::Keyword("any"))
+}
+
+pub fn get_fn_param_type(param: &TsFnParam) -> ParamType {
+    let (type_ann, is_optional, is_rest) = match param {
+        TsFnParam::Ident(ident) => (ident.type_ann.as_deref(), ident.optional, false),
+        TsFnParam::Array(array) => (array.type_ann.as_deref(), array.optional, false),
+        TsFnParam::Rest(rest) => (rest.type_ann.as_deref(), false, true),
+        TsFnParam::Object(object) => (object.type_ann.as_deref(), object.optional, false),
+    };
+
+    ParamType {
+        type_repr: get_type_ann_repr(type_ann),
+        is_optional,
+        is_rest,
+    }
+}
+
+/// The signature of a function declaration or expression. Async functions without a
+/// return type annotation return a `Promise` of an unknown type.
+pub fn get_function_type(function: &Function) -> FunctionType {
+    let returns = match (&function.return_type, function.is_async) {
+        (None, true) => TypeRepr::Reference {
+            name: "Promise".into(),
+            type_args: vec![TypeRepr::Keyword("any")],
+        },
+        (return_type, _) => get_type_ann_repr(return_type.as_deref()),
+    };
+
+    FunctionType {
+        params: function
+            .params
+            .iter()
+            .map(|param| get_pat_param_type(&param.pat))
+            .collect(),
+        returns,
+    }
+}
+
+/// The type of a function parameter pattern, where a default value makes it optional.
+pub fn get_pat_param_type(pattern: &Pat) -> ParamType {
+    match pattern {
+        Pat::Ident(ident) => get_fn_param_type(&TsFnParam::Ident(ident.to_owned())),
+        Pat::Array(array) => get_fn_param_type(&TsFnParam::Array(array.to_owned())),
+        Pat::Rest(rest) => get_fn_param_type(&TsFnParam::Rest(rest.to_owned())),
+        Pat::Object(object) => get_fn_param_type(&TsFnParam::Object(object.to_owned())),
+        Pat::Assign(assign) => ParamType {
+            is_optional: true,
+            ..get_pat_param_type(&assign.left)
+        },
+        Pat::Invalid(_) | Pat::Expr(_) => ParamType {
+            type_repr: TypeRepr::Keyword("any"),
+            is_optional: false,
+            is_rest: false,
+        },
+    }
+}
+
+fn get_type_params(type_params: Option<&TsTypeParamDecl>) -> Vec<TypeParam> {
+    let Some(type_params) = type_params else {
+        return Vec::new();
+    };
+
+    type_params
+        .params
+        .iter()
+        .map(|param| TypeParam {
+            name: param.name.sym.to_string(),
+            has_default: param.default.is_some(),
+        })
+        .collect()
+}
+
+fn get_object_type(members: &[TsTypeElement]) -> ObjectType {
+    let mut object = ObjectType::default();
+
+    for member in members {
+        match member {
+            TsTypeElement::TsPropertySignature(property) => {
+                if let Some(name) = get_property_key_name(&property.key, property.computed) {
+                    let property = PropertyType {
+                        type_repr: get_type_ann_repr(property.type_ann.as_deref()),
+                        is_optional: property.optional,
+                        is_readonly: property.readonly,
+                    };
+
+                    object.properties.insert(name, property);
+                }
+            }
+            TsTypeElement::TsMethodSignature(method) => {
+                if let Some(name) = get_property_key_name(&method.key, method.computed) {
+                    let function = FunctionType {
+                        params: method.params.iter().map(get_fn_param_type).collect(),
+                        returns: get_type_ann_repr(method.type_ann.as_deref()),
+                    };
+
+                    let property = PropertyType {
+                        type_repr: TypeRepr::Function(Box::new(function)),
+                        is_optional: method.optional,
+                        is_readonly: method.readonly,
+                    };
+
+                    object.properties.insert(name, property);
+                }
+            }
+            TsTypeElement::TsGetterSignature(getter) => {
+                if let Some(name) = get_property_key_name(&getter.key, getter.computed) {
+                    let property = PropertyType {
+                        type_repr: get_type_ann_repr(getter.type_ann.as_deref()),
+                        is_optional: getter.optional,
+                        is_readonly: true,
+                    };
+
+                    object.properties.insert(name, property);
+                }
+            }
+            TsTypeElement::TsIndexSignature(index) => {
+                let key_type = index
+                    .params
+                    .first()
+                    .map(|param| get_fn_param_type(param).type_repr)
+                    .unwrap_or(TypeRepr::Keyword("string"));
+
+                object.index_signatures.insert(
+                    key_type.to_string(),
+                    get_type_ann_repr(index.type_ann.as_deref()),
+                );
+            }
+            TsTypeElement::TsCallSignatureDecl(call) => {
+                object.call_signatures.push(FunctionType {
+                    params: call.params.iter().map(get_fn_param_type).collect(),
+                    returns: get_type_ann_repr(call.type_ann.as_deref()),
+                });
+            }
+            // Setters & construct signatures are not modeled.
+            _ => {}
+        }
+    }
+
+    object
+}
+
+/// Flattens nested unions, and orders & deduplicates their members, so that equal unions
+/// compare equal.
+fn get_union_repr(members: Vec<TypeRepr>) -> TypeRepr {
+    let mut flattened_members: Vec<TypeRepr> = Vec::new();
+
+    for member in members {
+        let nested_members = match member {
+            TypeRepr::Union(nested_members) => nested_members,
+            member => vec![member],
+        };
+
+        for nested_member in nested_members {
+            if !flattened_members.contains(&nested_member) {
+                flattened_members.push(nested_member);
+            }
+        }
+    }
+
+    flattened_members.sort_by_cached_key(|member| member.to_string());
+
+    if flattened_members.len().eq(&1) {
+        return flattened_members.remove(0);
+    }
+
+    TypeRepr::Union(flattened_members)
+}
+
+fn get_keyword_name(kind: &TsKeywordTypeKind) -> &'static str {
+    match kind {
+        TsKeywordTypeKind::TsAnyKeyword => "any",
+        TsKeywordTypeKind::TsUnknownKeyword => "unknown",
+        TsKeywordTypeKind::TsNumberKeyword => "number",
+        TsKeywordTypeKind::TsObjectKeyword => "object",
+        TsKeywordTypeKind::TsBooleanKeyword => "boolean",
+        TsKeywordTypeKind::TsBigIntKeyword => "bigint",
+        TsKeywordTypeKind::TsStringKeyword => "string",
+        TsKeywordTypeKind::TsSymbolKeyword => "symbol",
+        TsKeywordTypeKind::TsVoidKeyword => "void",
+        TsKeywordTypeKind::TsUndefinedKeyword => "undefined",
+        TsKeywordTypeKind::TsNullKeyword => "null",
+        TsKeywordTypeKind::TsNeverKeyword => "never",
+        TsKeywordTypeKind::TsIntrinsicKeyword => "intrinsic",
+    }
+}
+
+fn get_literal_keyword(literal: &str) -> &'static str {
+    if literal.starts_with('"') {
+        "string"
+    } else if literal.eq("true") || literal.eq("false") {
+        "boolean"
+    } else if literal.ends_with('n') {
+        "bigint"
+    } else {
+        "number"
+    }
+}
+
+fn get_entity_name(name: &TsEntityName) -> String {
+    match name {
+        TsEntityName::Ident(ident) => ident.sym.to_string(),
+        TsEntityName::TsQualifiedName(qualified) => {
+            format!(
+                "{}.{}",
+                get_entity_name(&qualified.left),
+                qualified.right.sym
+            )
+        }
+    }
+}
+
+fn get_expression_name(expression: &Expr) -> String {
+    match expression {
+        Expr::Ident(ident) => ident.sym.to_string(),
+        Expr::Member(member) => match member.prop.as_ident() {
+            Some(prop) => format!("{}.{}", get_expression_name(&member.obj), prop.sym),
+            None => get_expression_name(&member.obj),
+        },
+        _ => String::from("?"),
+    }
+}
+
+fn get_property_key_name(key: &Expr, is_computed: bool) -> Option<String> {
+    match key {
+        Expr::Ident(ident) if !is_computed => Some(ident.sym.to_string()),
+        Expr::Lit(Lit::Str(string)) => Some(string.value.to_string()),
+        Expr::Lit(Lit::Num(number)) => Some(number.value.to_string()),
+        _ => None,
+    }
+}
+
+impl Display for TypeRepr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeRepr::Keyword(keyword) => write!(formatter, "{keyword}"),
+            TypeRepr::Literal(literal) => write!(formatter, "{literal}"),
+            TypeRepr::Reference { name, type_args } if type_args.is_empty() => {
+                write!(formatter, "{name}")
+            }
+            TypeRepr::Reference { name, type_args } => {
+                write!(formatter, "{name}<{}>", join_types(type_args, ", "))
+            }
+            TypeRepr::Array(element) => match element.as_ref() {
+                TypeRepr::Union(_) | TypeRepr::Intersection(_) | TypeRepr::Function(_) => {
+                    write!(formatter, "({element})[]")
+                }
+                _ => write!(formatter, "{element}[]"),
+            },
+            TypeRepr::Tuple(elements) => write!(formatter, "[{}]", join_types(elements, ", ")),
+            TypeRepr::Union(members) => write!(formatter, "{}", join_types(members, " | ")),
+            TypeRepr::Intersection(members) => {
+                write!(formatter, "{}", join_types(members, " & "))
+            }
+            TypeRepr::Function(function) => write!(formatter, "{function}"),
+            TypeRepr::Object(object) => {
+                let mut members: Vec<String> = Vec::new();
+
+                for (name, property) in object.properties.iter() {
+                    let readonly = if property.is_readonly {
+                        "readonly "
+                    } else {
+                        ""
+                    };
+                    let optional = if property.is_optional { "?" } else { "" };
+
+                    members.push(format!(
+                        "{readonly}{name}{optional}: {}",
+                        property.type_repr
+                    ));
+                }
+
+                for (key, value) in object.index_signatures.iter() {
+                    members.push(format!("[key: {key}]: {value}"));
+                }
+
+                if members.is_empty() {
+                    write!(formatter, "{{}}")
+                } else {
+                    write!(formatter, "{{ {} }}", members.join("; "))
+                }
+            }
+            TypeRepr::Opaque(_) => write!(formatter, "a computed type"),
+        }
+    }
+}
+
+impl Display for FunctionType {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|param| {
+                let rest = if param.is_rest { "..." } else { "" };
+                let optional = if param.is_optional { "?" } else { "" };
+
+                format!("{rest}{}{optional}", param.type_repr)
+            })
+            .collect();
+
+        write!(formatter, "({}) => {}", params.join(", "), self.returns)
+    }
+}
+
+fn join_types(types: &[TypeRepr], separator: &str) -> String {
+    types
+        .iter()
+        .map(|type_repr| type_repr.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecma::parser::parse_module_source;
+    use std::path::Path;
+    use swc_ecma_ast::{Decl, ModuleItem, Stmt};
+
+    fn get_type_declaration(source: &str) -> TypeDeclaration {
+        let (module, _) = parse_module_source(Path::new("index.ts"), source.into()).unwrap();
+
+        match module.body.into_iter().next() {
+            Some(ModuleItem::Stmt(Stmt::Decl(Decl::TsInterface(interface)))) => {
+                TypeDeclaration::from_interface(&interface)
+            }
+            Some(ModuleItem::Stmt(Stmt::Decl(Decl::TsTypeAlias(type_alias)))) => {
+                TypeDeclaration::from_type_alias(&type_alias)
+            }
+            _ => panic!("expected an interface or type alias"),
+        }
+    }
+
+    fn get_type(type_source: &str) -> TypeRepr {
+        get_type_declaration(&format!("type T = {type_source};")).shape
+    }
+
+    fn is_assignable(source: &str, target: &str) -> bool {
+        get_type(source).is_assignable_to(&get_type(target))
+    }
+
+    #[test]
+    fn rest_params_accept_each_remaining_argument() {
+        assert!(is_assignable(
+            "(...args: string[]) => void",
+            "(a: string) => void"
+        ));
+        assert!(is_assignable(
+            "(a: string, ...args: Array<string>) => void",
+            "(a: string, b: string, c?: string) => void"
+        ));
+        assert!(is_assignable(
+            "(...args: string[]) => void",
+            "(...args: string[]) => void"
+        ));
+        assert!(!is_assignable(
+            "(...args: string[]) => void",
+            "(a: string, b: number) => void"
+        ));
+        assert!(!is_assignable(
+            "(...args: [string, number]) => void",
+            "(a: string, b: string) => void"
+        ));
+    }
+
+    #[test]
+    fn params_the_target_may_omit_must_be_optional() {
+        assert!(!is_assignable(
+            "(a: string) => void",
+            "(a?: string) => void"
+        ));
+        assert!(!is_assignable(
+            "(a: string) => void",
+            "(...args: string[]) => void"
+        ));
+        assert!(!is_assignable("(a: string) => void", "() => void"));
+        assert!(is_assignable("(a?: string) => void", "(a: string) => void"));
+        assert!(is_assignable("(a?: string) => void", "() => void"));
+        assert!(is_assignable("() => void", "(a: string) => void"));
+    }
+
+    #[test]
+    fn params_are_contravariant_and_returns_covariant() {
+        assert!(is_assignable(
+            "(a: string | number) => string",
+            "(a: string) => string | number"
+        ));
+        assert!(!is_assignable(
+            "(a: string) => void",
+            "(a: string | number) => void"
+        ));
+        assert!(!is_assignable("() => string | number", "() => string"));
+        assert!(is_assignable("() => string", "() => void"));
+    }
+
+    #[test]
+    fn objects_without_a_required_property_are_not_assignable() {
+        assert!(is_assignable("{ a: string; b: number }", "{ a: string }"));
+        assert!(is_assignable("{ a: string }", "{ a: string; b?: number }"));
+        assert!(!is_assignable("{ a: string }", "{ a: string; b: number }"));
+    }
+
+    #[test]
+    fn optional_properties_are_not_assignable_to_required_ones() {
+        assert!(is_assignable("{ a: string }", "{ a?: string }"));
+        assert!(!is_assignable("{ a?: string }", "{ a: string }"));
+    }
+
+    #[test]
+    fn narrower_types_are_assignable_to_wider_ones() {
+        assert!(is_assignable("string", "string | number"));
+        assert!(is_assignable("\"a\" | \"b\"", "string"));
+        assert!(is_assignable("boolean", "true | false | null"));
+        assert!(is_assignable("[string, number]", "(string | number)[]"));
+        assert!(is_assignable("Map<string, 1>", "Map<string, number>"));
+        assert!(is_assignable(
+            "{ a: \"x\"; b: string[] }",
+            "{ a: string; b: (string | null)[] }"
+        ));
+        assert!(!is_assignable("string | number", "string"));
+        assert!(!is_assignable("string", "\"a\""));
+        assert!(!is_assignable("Map<string, number>", "Map<string, 1>"));
+        assert!(!is_assignable("{ a: string | null }", "{ a: string }"));
+    }
+
+    #[test]
+    fn objects_without_an_index_signature_are_not_assignable() {
+        assert!(is_assignable(
+            "{ [key: string]: \"a\" }",
+            "{ [key: string]: string }"
+        ));
+        assert!(!is_assignable("{ a: string }", "{ [key: string]: string }"));
+        assert!(!is_assignable(
+            "{ [key: number]: string }",
+            "{ [key: string]: string }"
+        ));
+    }
+
+    #[test]
+    fn type_params_record_whether_they_have_a_default() {
+        let declaration = get_type_declaration("interface T<A, B = string> { a: A; b: B }");
+        let type_params: Vec<(&str, bool)> = declaration
+            .type_params
+            .iter()
+            .map(|type_param| (type_param.name.as_str(), type_param.has_default))
+            .collect();
+
+        assert_eq!(type_params, [("A", false), ("B", true)]);
+    }
+}
@@ -0,0 +1,430 @@
+use crate::ecma::entity::get_object_property_name;
+use std::collections::{BTreeMap, HashMap};
+use strum_macros::Display;
+use swc_ecma_ast::{BinaryOp, Callee, Expr, Lit, MemberProp, Prop, TsEnumDecl, UnaryOp};
+
+/// A value that is known without running the module, from the literal it's initialized with.
+#[derive(Display, PartialEq)]
+pub enum ConstantValue {
+    /// A primitive, or an array of primitives, as written in the source.
+    #[strum(to_string = "{value}")]
+    Literal { value: String },
+    /// An object literal's properties, with the literal value of those that have one.
+    #[strum(serialize = "an object")]
+    Object {
+        properties: BTreeMap<String, Option<String>>,
+        is_frozen: bool,
+    },
+}
+
+pub fn get_constant_value(expression: &Expr) -> Option<ConstantValue> {
+    let expression = expression.unwrap_parens();
+
+    if let Some(literal) = get_literal_value(expression) {
+        return Some(ConstantValue::Literal { value: literal });
+    }
+
+    let (expression, is_frozen) = match get_frozen_object(expression) {
+        Some(frozen_expression) => (frozen_expression, true),
+        None => (expression, false),
+    };
+
+    let object = expression.as_object()?;
+
+    // The keys of spread objects are unknown, so nothing can be said about the others.
+    if object.props.iter().any(|property| property.is_spread()) {
+        return None;
+    }
+
+    let mut properties = BTreeMap::new();
+
+    for property in object.props.iter() {
+        let Some(name) = get_object_property_name(property) else {
+            continue;
+        };
+
+        let value = match property.as_prop().map(|property| property.as_ref()) {
+            Some(Prop::KeyValue(property)) => get_literal_value(&property.value),
+            _ => None,
+        };
+
+        properties.insert(name, value);
+    }
+
+    Some(ConstantValue::Object {
+        properties,
+        is_frozen,
+    })
+}
+
+fn get_literal_value(expression: &Expr) -> Option<String> {
+    match expression.unwrap_parens() {
+        Expr::Lit(literal) => match literal {
+            Lit::Str(string) => Some(json::stringify(string.value.as_ref())),
+            Lit::Num(number) => Some(number.value.to_string()),
+            Lit::Bool(boolean) => Some(boolean.value.to_string()),
+            Lit::BigInt(big_int) => Some(format!("{}n", big_int.value)),
+            Lit::Null(_) => Some("null".into()),
+            _ => None,
+        },
+        Expr::Unary(unary) if unary.op.eq(&UnaryOp::Minus) => {
+            let number = unary.arg.as_lit()?;
+
+            match number {
+                Lit::Num(number) => Some(format!("-{}", number.value)),
+                Lit::BigInt(big_int) => Some(format!("-{}n", big_int.value)),
+                _ => None,
+            }
+        }
+        Expr::Tpl(template) if template.exprs.is_empty() => {
+            let quasi = template.quasis.first()?;
+            let cooked = quasi.cooked.as_ref()?;
+
+            Some(json::stringify(cooked.as_ref()))
+        }
+        Expr::Array(array) => {
+            let mut elements = Vec::with_capacity(array.elems.len());
+
+            for element in array.elems.iter() {
+                let element = element.as_ref()?;
+
+                if element.spread.is_some() {
+                    return None;
+                }
+
+                elements.push(get_literal_value(&element.expr)?);
+            }
+
+            Some(format!("[{}]", elements.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// The object literal passed to `Object.freeze`, if that's what the expression is.
+fn get_frozen_object(expression: &Expr) -> Option<&Expr> {
+    let call = expression.as_call()?;
+
+    let Callee::Expr(callee) = &call.callee else {
+        return None;
+    };
+
+    let callee = callee.as_member()?;
+    let is_object_freeze = callee
+        .obj
+        .as_ident()
+        .is_some_and(|ident| ident.sym.eq("Object"))
+        && callee
+            .prop
+            .as_ident()
+            .is_some_and(|ident| ident.sym.eq("freeze"));
+
+    if !is_object_freeze || call.args.len().ne(&1) || call.args[0].spread.is_some() {
+        return None;
+    }
+
+    Some(call.args[0].expr.unwrap_parens())
+}
+
+/// The value of each enum member, in declaration order. Members whose initializer can't be
+/// evaluated without running the module, and those following them, have no known value.
+pub fn get_enum_member_values(enum_declaration: &TsEnumDecl) -> Vec<(String, Option<String>)> {
+    let enum_name = enum_declaration.id.sym.to_string();
+    let mut evaluated_members: HashMap<String, EnumMemberValue> = HashMap::new();
+    let mut previous_value = None;
+    let mut member_values = Vec::with_capacity(enum_declaration.members.len());
+
+    for (index, member) in enum_declaration.members.iter().enumerate() {
+        let member_name = AsRef::<str>::as_ref(member.id.as_ref()).to_owned();
+
+        let value = match &member.init {
+            Some(init) => evaluate_enum_initializer(init, &enum_name, &evaluated_members),
+            // Members without an initializer follow the previous numeric member.
+            None if index.eq(&0) => Some(EnumMemberValue::Number(0.0)),
+            None => match previous_value {
+                Some(EnumMemberValue::Number(number)) => {
+                    Some(EnumMemberValue::Number(number + 1.0))
+                }
+                _ => None,
+            },
+        };
+
+        if let Some(value) = &value {
+            evaluated_members.insert(member_name.to_owned(), value.to_owned());
+        }
+
+        member_values.push((member_name, value.as_ref().map(EnumMemberValue::to_literal)));
+        previous_value = value;
+    }
+
+    member_values
+}
+
+#[derive(Clone)]
+enum EnumMemberValue {
+    Number(f64),
+    String(String),
+}
+
+impl EnumMemberValue {
+    /// The value as it would be written in the source.
+    fn to_literal(&self) -> String {
+        match self {
+            EnumMemberValue::Number(number) => number.to_string(),
+            EnumMemberValue::String(string) => json::stringify(string.as_str()),
+        }
+    }
+
+    /// The value as it would be converted to a string at runtime.
+    fn to_runtime_string(&self) -> String {
+        match self {
+            EnumMemberValue::Number(number) => number.to_string(),
+            EnumMemberValue::String(string) => string.to_owned(),
+        }
+    }
+}
+
+fn evaluate_enum_initializer(
+    expression: &Expr,
+    enum_name: &str,
+    evaluated_members: &HashMap<String, EnumMemberValue>,
+) -> Option<EnumMemberValue> {
+    let evaluate = |expression| evaluate_enum_initializer(expression, enum_name, evaluated_members);
+
+    match expression.unwrap_parens() {
+        Expr::Lit(Lit::Num(number)) => Some(EnumMemberValue::Number(number.value)),
+        Expr::Lit(Lit::Str(string)) => Some(EnumMemberValue::String(string.value.to_string())),
+        Expr::Tpl(template) if template.exprs.is_empty() => {
+            let cooked = template.quasis.first()?.cooked.as_ref()?;
+
+            Some(EnumMemberValue::String(cooked.to_string()))
+        }
+        // Other members are referenced either directly, or through the enum itself.
+        Expr::Ident(ident) => evaluated_members.get(ident.sym.as_str()).cloned(),
+        Expr::Member(member) => {
+            let is_enum_member = member
+                .obj
+                .as_ident()
+                .is_some_and(|ident| ident.sym.eq(enum_name));
+
+            let member_name = match &member.prop {
+                MemberProp::Ident(ident) => ident.sym.to_string(),
+                MemberProp::Computed(computed) => {
+                    computed.expr.as_lit().and_then(|literal| match literal {
+                        Lit::Str(string) => Some(string.value.to_string()),
+                        _ => None,
+                    })?
+                }
+                MemberProp::PrivateName(_) => return None,
+            };
+
+            if !is_enum_member {
+                return None;
+            }
+
+            evaluated_members.get(&member_name).cloned()
+        }
+        Expr::Unary(unary) => {
+            let EnumMemberValue::Number(number) = evaluate(&unary.arg)? else {
+                return None;
+            };
+
+            match unary.op {
+                UnaryOp::Minus => Some(EnumMemberValue::Number(-number)),
+                UnaryOp::Plus => Some(EnumMemberValue::Number(number)),
+                UnaryOp::Tilde => Some(EnumMemberValue::Number(!to_int32(number) as f64)),
+                _ => None,
+            }
+        }
+        Expr::Bin(binary) => {
+            let left = evaluate(&binary.left)?;
+            let right = evaluate(&binary.right)?;
+
+            let (left, right) = match (left, right) {
+                (EnumMemberValue::Number(left), EnumMemberValue::Number(right)) => (left, right),
+                (left, right) if binary.op.eq(&BinaryOp::Add) => {
+                    return Some(EnumMemberValue::String(format!(
+                        "{}{}",
+                        left.to_runtime_string(),
+                        right.to_runtime_string()
+                    )))
+                }
+                _ => return None,
+            };
+
+            let number = match binary.op {
+                BinaryOp::Add => left + right,
+                BinaryOp::Sub => left - right,
+                BinaryOp::Mul => left * right,
+                BinaryOp::Div => left / right,
+                BinaryOp::Mod => left % right,
+                BinaryOp::Exp => left.powf(right),
+                BinaryOp::BitOr => (to_int32(left) | to_int32(right)) as f64,
+                BinaryOp::BitAnd => (to_int32(left) & to_int32(right)) as f64,
+                BinaryOp::BitXor => (to_int32(left) ^ to_int32(right)) as f64,
+                BinaryOp::LShift => to_int32(left).wrapping_shl(to_int32(right) as u32) as f64,
+                BinaryOp::RShift => to_int32(left).wrapping_shr(to_int32(right) as u32) as f64,
+                BinaryOp::ZeroFillRShift => {
+                    (to_int32(left) as u32).wrapping_shr(to_int32(right) as u32) as f64
+                }
+                _ => return None,
+            };
+
+            Some(EnumMemberValue::Number(number))
+        }
+        _ => None,
+    }
+}
+
+/// Converts a number the way bitwise operators do (ToInt32), wrapping it modulo 2^32
+/// rather than saturating. NaN and infinities become 0.
+fn to_int32(number: f64) -> i32 {
+    (number % 4_294_967_296.0) as i64 as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecma::parser::parse_module_source;
+    use std::path::Path;
+    use swc_ecma_ast::{Decl, ModuleItem, Stmt};
+
+    fn parse_declaration(source: &str) -> Decl {
+        let (module, _) = parse_module_source(Path::new("index.ts"), source.into()).unwrap();
+
+        match module.body.into_iter().next() {
+            Some(ModuleItem::Stmt(Stmt::Decl(declaration))) => declaration,
+            _ => panic!("expected a declaration"),
+        }
+    }
+
+    fn get_initializer_value(expression: &str) -> Option<ConstantValue> {
+        let declaration = parse_declaration(&format!("const value = {expression};"));
+        let init = declaration.as_var().unwrap().decls[0]
+            .init
+            .as_ref()
+            .unwrap();
+
+        get_constant_value(init)
+    }
+
+    fn get_literal(expression: &str) -> Option<String> {
+        match get_initializer_value(expression) {
+            Some(ConstantValue::Literal { value }) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_member_values(enum_source: &str) -> Vec<(String, Option<String>)> {
+        let declaration = parse_declaration(enum_source);
+
+        get_enum_member_values(declaration.as_ts_enum().unwrap())
+    }
+
+    fn get_values(member_values: &[(String, Option<String>)]) -> Vec<Option<&str>> {
+        member_values
+            .iter()
+            .map(|(_, value)| value.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn literals_are_written_as_in_the_source() {
+        assert_eq!(get_literal(r#"("a")"#), Some(r#""a""#.into()));
+        assert_eq!(get_literal("`b`"), Some(r#""b""#.into()));
+        assert_eq!(get_literal("-1.5"), Some("-1.5".into()));
+        assert_eq!(get_literal("10n"), Some("10n".into()));
+        assert_eq!(get_literal("null"), Some("null".into()));
+        assert_eq!(
+            get_literal("[1, true, 'c']"),
+            Some(r#"[1, true, "c"]"#.into())
+        );
+    }
+
+    #[test]
+    fn expressions_have_no_constant_value() {
+        assert!(get_initializer_value("`a${b}`").is_none());
+        assert!(get_initializer_value("[...items]").is_none());
+        assert!(get_initializer_value("{ ...defaults, a: 1 }").is_none());
+        assert!(get_initializer_value("1 + 2").is_none());
+    }
+
+    #[test]
+    fn objects_list_the_literal_value_of_their_properties() {
+        let value = get_initializer_value("Object.freeze({ a: 1, b: other, c() {} })");
+
+        let Some(ConstantValue::Object {
+            properties,
+            is_frozen,
+        }) = value
+        else {
+            panic!("expected an object");
+        };
+
+        assert!(is_frozen);
+        assert_eq!(
+            properties,
+            BTreeMap::from([
+                ("a".into(), Some("1".into())),
+                ("b".into(), None),
+                ("c".into(), None),
+            ])
+        );
+    }
+
+    #[test]
+    fn enum_members_follow_the_previous_numeric_member() {
+        let member_values = get_member_values("enum E { A, B, C = 10, D, E = 'e', F }");
+
+        assert_eq!(member_values[0].0, "A");
+        assert_eq!(
+            get_values(&member_values),
+            [
+                Some("0"),
+                Some("1"),
+                Some("10"),
+                Some("11"),
+                Some(r#""e""#),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_initializers_reference_other_members() {
+        let member_values = get_member_values(
+            "enum E { A = 1 << 2, B = A | 1, C = E.B * 2, D = E['A'] + 'x', E = unknown, F }",
+        );
+
+        assert_eq!(
+            get_values(&member_values),
+            [
+                Some("4"),
+                Some("5"),
+                Some("10"),
+                Some(r#""4x""#),
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_bitwise_operators_wrap_like_int32() {
+        let member_values = get_member_values(
+            "enum E { A = 0xFFFFFFFF | 0, B = ~0xFFFFFFFF, C = 1 << 31, D = -1 >>> 0, E = 1 << -1, F = 0x100000001 & 3 }",
+        );
+
+        assert_eq!(
+            get_values(&member_values),
+            [
+                Some("-1"),
+                Some("0"),
+                Some("-2147483648"),
+                Some("4294967295"),
+                Some("-2147483648"),
+                Some("1")
+            ]
+        );
+    }
+}
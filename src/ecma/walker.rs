@@ -1,90 +1,263 @@
-use crate::ecma::entity::EntityDeclaration;
-use crate::ecma::parser::parse_import;
+use crate::ecma::docs::{get_doc_tags, DocTags};
+use crate::ecma::entity::{get_object_property_name, get_var_declarations, EntityDeclaration};
+use crate::ecma::graph::{is_declaration_file, ImportName, IndirectExport, ModuleRecord};
 use anyhow::{bail, Context, Result};
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+use swc_common::comments::SingleThreadedComments;
+use swc_common::{BytePos, Spanned};
 use swc_ecma_ast::{
-    ExportAll, ExportDecl, ExportDefaultDecl, ExportDefaultExpr, ExportNamedSpecifier,
-    ExportSpecifier, Module, ModuleExportName, NamedExport,
+    AssignExpr, Callee, Decl, ExportAll, ExportDecl, ExportDefaultDecl, ExportDefaultExpr,
+    ExportNamedSpecifier, Expr, Ident, ImportDecl, ImportSpecifier, Lit, MemberExpr, MemberProp,
+    Module, ModuleDecl, ModuleExportName, ModuleItem, NamedExport, Pat, Prop, PropName,
+    PropOrSpread, SimpleAssignTarget, Stmt, TsExportAssignment, TsModuleDecl, TsModuleName,
+    TsNamespaceBody, TsNamespaceDecl, VarDeclarator,
 };
 
-pub type ExternalSpecifiers<'module> = HashMap<PathBuf, Vec<&'module ExportSpecifier>>;
+/// Imported bindings by local name, with the specifier they are imported from.
+pub type Imports = HashMap<String, (String, ImportName)>;
 pub type Declarations = HashMap<String, EntityDeclaration>;
 pub type DeclarationsWithExport<'module> = Vec<&'module ExportDecl>;
 pub type ExportsFacadeAll<'module> = Vec<&'module ExportAll>;
 pub type ExportsNamed<'module> = Vec<&'module NamedExport>;
 pub type DefaultExportDeclaration<'module> = Option<&'module ExportDefaultDecl>;
 pub type DefaultExportExpression<'module> = Option<&'module ExportDefaultExpr>;
+/// Every declaration of each namespace in the module, as namespaces can be merged.
+pub type Namespaces<'module> = HashMap<String, Vec<&'module TsModuleDecl>>;
+pub type ExportAssignment<'module> = Option<&'module TsExportAssignment>;
+/// Doc tags by export name, for the exports that have any.
+pub type ExportDocTags = HashMap<String, DocTags>;
+
+/// Doc tags of the declarations of a module, by the name they are declared with, or exported
+/// with for namespace members & default exports.
+struct DeclarationTags<'module> {
+    comments: &'module SingleThreadedComments,
+    tags: HashMap<String, DocTags>,
+}
+
+impl DeclarationTags<'_> {
+    /// Records the tags of the doc comment leading a declaration, unless an earlier
+    /// declaration of the same name, e.g. the first overload, has some already.
+    fn add(&mut self, name: String, position: BytePos) {
+        let doc_tags = get_doc_tags(self.comments, position);
+
+        if doc_tags.ne(&DocTags::default()) {
+            self.tags.entry(name).or_insert(doc_tags);
+        }
+    }
+}
+
+/// Collects the exports of a module, without following the modules it re-exports from.
+pub fn get_module_record(
+    module_path: &Path,
+    module: &Module,
+    comments: &SingleThreadedComments,
+) -> Result<ModuleRecord> {
+    // Modules without any import or export declarations are treated as CommonJS, which
+    // is how dual packages commonly publish their `require` builds.
+    if !module.body.iter().any(|item| item.is_module_decl()) {
+        return Ok(get_commonjs_module_record(module));
+    }
 
-pub fn get_exports_in_module(
-    base_import_path: PathBuf,
-    module: Module,
-) -> Result<(Option<EntityDeclaration>, Declarations)> {
     let (
-        mut declarations,
+        declarations,
+        imports,
         declarations_with_export,
         exports_facade_all,
         exports_named,
         default_export_declaration,
         default_export_expression,
-    ) = get_items_in_module(&module).with_context(|| {
+    ) = get_items_in_module(module).with_context(|| {
         format!(
             "Failed to walk through relevant items in module: {}",
-            base_import_path.display()
+            module_path.display()
         )
     })?;
 
-    let (mut named_exports, named_facade_exports) =
-        get_named_export_declarations(&declarations_with_export, &exports_named, &mut declarations)
-            .with_context(|| {
-                format!(
-                    "Failed to extract named exports in module: {}",
-                    base_import_path.display()
-                )
-            })?;
+    let namespaces = get_namespaces(module);
+    let export_assignment = get_export_assignment(module);
+    let mut declaration_tags = get_declaration_tags(module, comments);
+
+    // Members of namespaces in declaration files are exported without the keyword.
+    let is_ambient = is_declaration_file(module_path);
+
+    let (mut local_exports, indirect_exports) = get_named_export_declarations(
+        &declarations_with_export,
+        &exports_named,
+        &declarations,
+        &imports,
+        &namespaces,
+        is_ambient,
+        &mut declaration_tags,
+    )
+    .with_context(|| {
+        format!(
+            "Failed to extract named exports in module: {}",
+            module_path.display()
+        )
+    })?;
 
     let default_export = get_default_export_declaration(
         &default_export_declaration,
         &default_export_expression,
-        declarations,
+        &export_assignment,
+        &declarations,
     )
     .with_context(|| {
         format!(
             "Failed to extract default export in module: {}",
-            base_import_path.display()
+            module_path.display()
         )
     })?;
 
-    add_export_all_facade_exports(&base_import_path, exports_facade_all, &mut named_exports)
-        .with_context(|| {
-            format!(
-                "Failed to walk through export all (*) exports in module: {}",
-                base_import_path.display()
-            )
-        })?;
-
-    add_named_facade_exports(&base_import_path, named_facade_exports, &mut named_exports)
-        .with_context(|| {
-            format!(
-                "Failed to walk through facade named exports in module: {}",
-                base_import_path.display()
-            )
-        })?;
-
-    Ok((default_export, named_exports))
+    if let Some(default_export) = default_export {
+        local_exports.insert("default".into(), default_export);
+    }
+
+    // The members of a namespace assigned with `export =` are properties of
+    // `module.exports`, which CommonJS interop exposes as named exports.
+    if let Some(export_assignment) = export_assignment {
+        if let Some(ident) = export_assignment.expr.unwrap_parens().as_ident() {
+            for namespace in namespaces.get(ident.sym.as_str()).into_iter().flatten() {
+                add_namespace_member_declarations(
+                    "",
+                    namespace.body.as_ref().unwrap(),
+                    is_ambient || namespace.declare,
+                    &mut local_exports,
+                    &mut declaration_tags,
+                )?;
+            }
+        }
+    }
+
+    if is_ambient {
+        local_exports = local_exports
+            .into_iter()
+            .map(|(name, declaration)| (name, declaration.in_declaration_file()))
+            .collect();
+    }
+
+    let star_exports = exports_facade_all
+        .iter()
+        .map(|export| export.src.value.to_string())
+        .collect();
+
+    Ok(ModuleRecord {
+        indirect_exports,
+        star_exports,
+        global_name: get_global_name(module),
+        doc_tags: get_export_doc_tags(&local_exports, &declaration_tags.tags),
+        local_exports,
+    })
+}
+
+fn get_declaration_tags<'module>(
+    module: &Module,
+    comments: &'module SingleThreadedComments,
+) -> DeclarationTags<'module> {
+    let mut declaration_tags = DeclarationTags {
+        comments,
+        tags: HashMap::new(),
+    };
+
+    for item in module.body.iter() {
+        let position = item.span_lo();
+
+        let declaration = match item {
+            ModuleItem::Stmt(Stmt::Decl(declaration)) => declaration,
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => &export.decl,
+            ModuleItem::ModuleDecl(
+                ModuleDecl::ExportDefaultDecl(_)
+                | ModuleDecl::ExportDefaultExpr(_)
+                | ModuleDecl::TsExportAssignment(_),
+            ) => {
+                declaration_tags.add("default".into(), position);
+                continue;
+            }
+            _ => continue,
+        };
+
+        let declarations = match declaration {
+            Decl::Var(var) => get_var_declarations(var),
+            Decl::Using(_) => continue,
+            declaration => EntityDeclaration::from(declaration).into_iter().collect(),
+        };
+
+        for declaration in declarations {
+            declaration_tags.add(declaration.name().to_owned(), position);
+        }
+    }
+
+    declaration_tags
+}
+
+/// The doc tags of each export. Namespace members without any inherit those of the
+/// namespaces they are declared in.
+fn get_export_doc_tags(
+    local_exports: &Declarations,
+    declaration_tags: &HashMap<String, DocTags>,
+) -> ExportDocTags {
+    let mut export_doc_tags = ExportDocTags::new();
+
+    for (export_name, declaration) in local_exports.iter() {
+        let own_doc_tags = match export_name.as_str() {
+            "default" => declaration_tags
+                .get("default")
+                .or(declaration_tags.get(declaration.name())),
+            name if name.contains('.') => declaration_tags.get(name),
+            _ => declaration_tags.get(declaration.name()),
+        };
+
+        let mut doc_tags = own_doc_tags.copied().unwrap_or_default();
+        let mut name = export_name.as_str();
+
+        while let Some((namespace_name, _)) = name.rsplit_once('.') {
+            if let Some(namespace_doc_tags) = declaration_tags.get(namespace_name) {
+                doc_tags.release_tag = doc_tags.release_tag.or(namespace_doc_tags.release_tag);
+                doc_tags.is_deprecated |= namespace_doc_tags.is_deprecated;
+            }
+
+            name = namespace_name;
+        }
+
+        if doc_tags.ne(&DocTags::default()) {
+            export_doc_tags.insert(export_name.to_owned(), doc_tags);
+        }
+    }
+
+    export_doc_tags
+}
+
+/// The `export =` assignment of `module.exports`, which CommonJS declaration files use.
+fn get_export_assignment(module: &Module) -> ExportAssignment<'_> {
+    module
+        .body
+        .iter()
+        .find_map(|item| item.as_module_decl()?.as_ts_export_assignment())
+}
+
+/// The global declared with `export as namespace`, which UMD declaration files use.
+fn get_global_name(module: &Module) -> Option<String> {
+    module.body.iter().find_map(|item| {
+        let namespace_export = item.as_module_decl()?.as_ts_namespace_export()?;
+
+        Some(namespace_export.id.sym.to_string())
+    })
 }
 
 fn get_items_in_module(
     module: &Module,
 ) -> Result<(
     Declarations,
-    DeclarationsWithExport,
-    ExportsFacadeAll,
-    ExportsNamed,
-    DefaultExportDeclaration,
-    DefaultExportExpression,
+    Imports,
+    DeclarationsWithExport<'_>,
+    ExportsFacadeAll<'_>,
+    ExportsNamed<'_>,
+    DefaultExportDeclaration<'_>,
+    DefaultExportExpression<'_>,
 )> {
     let mut declarations = Declarations::new();
+    let mut imports = Imports::new();
     let mut declarations_with_export = DeclarationsWithExport::new();
 
     let mut export_all_exports = ExportsFacadeAll::new();
@@ -100,30 +273,56 @@ fn get_items_in_module(
             if statement.is_decl() {
                 let declaration = statement.as_decl().unwrap();
                 let declaration = if declaration.is_var() {
-                    EntityDeclaration::from(declaration.as_var().unwrap().decls.last().unwrap())?
+                    for declaration in get_var_declarations(declaration.as_var().unwrap()) {
+                        declarations.insert(declaration.name().to_owned(), declaration);
+                    }
+
+                    continue;
                 } else if declaration.is_class() {
                     EntityDeclaration::from(declaration.as_class().unwrap())?
                 } else if declaration.is_fn_decl() {
                     EntityDeclaration::from(declaration.as_fn_decl().unwrap())?
                 } else if declaration.is_ts_module() {
-                    todo!("handle TS module declarations")
+                    let namespace = declaration.as_ts_module().unwrap();
+
+                    // Ambient modules & global augmentations describe other modules.
+                    if !is_namespace(namespace) {
+                        continue;
+                    }
+
+                    EntityDeclaration::from(namespace.as_ref())?
                 } else if declaration.is_ts_enum() {
-                    todo!("handle TS enum declarations")
+                    EntityDeclaration::from(declaration.as_ts_enum().unwrap().as_ref())?
                 } else if declaration.is_ts_interface() {
-                    todo!("handle TS interface declarations")
+                    EntityDeclaration::from(declaration.as_ts_interface().unwrap().as_ref())?
                 } else if declaration.is_ts_type_alias() {
-                    todo!("handle TS type alias declarations")
+                    EntityDeclaration::from(declaration.as_ts_type_alias().unwrap().as_ref())?
                 } else {
                     continue;
                 };
 
-                declarations.insert(declaration.name().to_owned(), declaration);
+                add_declaration(
+                    declaration.name().to_owned(),
+                    declaration,
+                    &mut declarations,
+                );
             }
         } else if item.is_module_decl() {
             let module_declaration = item.as_module_decl().unwrap();
 
-            if module_declaration.is_export_decl() {
-                declarations_with_export.push(module_declaration.as_export_decl().unwrap());
+            if module_declaration.is_import() {
+                add_import_bindings(module_declaration.as_import().unwrap(), &mut imports);
+            } else if module_declaration.is_export_decl() {
+                let export = module_declaration.as_export_decl().unwrap();
+
+                // Ambient modules & global augmentations describe other modules.
+                if let Some(namespace) = export.decl.as_ts_module() {
+                    if !is_namespace(namespace) {
+                        continue;
+                    }
+                }
+
+                declarations_with_export.push(export);
             } else if module_declaration.is_export_default_decl() {
                 default_export_declaration = module_declaration.as_export_default_decl();
             } else if module_declaration.is_export_default_expr() {
@@ -132,16 +331,13 @@ fn get_items_in_module(
                 export_all_exports.push(module_declaration.as_export_all().unwrap());
             } else if module_declaration.is_export_named() {
                 named_exports.push(module_declaration.as_export_named().unwrap());
-            } else if module_declaration.is_ts_export_assignment() {
-                todo!("handle TS export assignments")
-            } else if module_declaration.is_ts_namespace_export() {
-                todo!("handle TS namespace exports")
             }
         }
     }
 
     Ok((
         declarations,
+        imports,
         declarations_with_export,
         export_all_exports,
         named_exports,
@@ -150,55 +346,264 @@ fn get_items_in_module(
     ))
 }
 
+/// Adds a declaration, merging it with any previous one of the same name, e.g. overloads.
+fn add_declaration(name: String, declaration: EntityDeclaration, declarations: &mut Declarations) {
+    let declaration = match declarations.remove(&name) {
+        Some(existing_declaration) => existing_declaration.merge(declaration),
+        None => declaration,
+    };
+
+    declarations.insert(name, declaration);
+}
+
+fn is_namespace(declaration: &TsModuleDecl) -> bool {
+    declaration.id.is_ident() && !declaration.global && declaration.body.is_some()
+}
+
+fn get_namespaces(module: &Module) -> Namespaces<'_> {
+    let mut namespaces = Namespaces::new();
+
+    for item in module.body.iter() {
+        let declaration = match item {
+            ModuleItem::Stmt(Stmt::Decl(declaration)) => declaration,
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => &export.decl,
+            _ => continue,
+        };
+
+        if let Some(namespace) = declaration.as_ts_module() {
+            if let (TsModuleName::Ident(ident), true) = (&namespace.id, is_namespace(namespace)) {
+                namespaces
+                    .entry(ident.sym.to_string())
+                    .or_default()
+                    .push(namespace);
+            }
+        }
+    }
+
+    namespaces
+}
+
+/// Adds the members a namespace exports, named with the given prefix, including those of
+/// the namespaces nested in it. Ambient namespaces export all of their members.
+fn add_namespace_member_declarations(
+    prefix: &str,
+    body: &TsNamespaceBody,
+    is_ambient: bool,
+    member_declarations: &mut Declarations,
+    declaration_tags: &mut DeclarationTags,
+) -> Result<()> {
+    let items = match body {
+        TsNamespaceBody::TsModuleBlock(block) => &block.body,
+        // `namespace A.B {}` declares `B` as the only member of `A`.
+        TsNamespaceBody::TsNamespaceDecl(nested_namespace) => {
+            return add_nested_namespace_declarations(
+                prefix,
+                nested_namespace,
+                is_ambient,
+                member_declarations,
+                declaration_tags,
+            );
+        }
+    };
+
+    for item in items.iter() {
+        let position = item.span_lo();
+
+        let declaration = match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => &export.decl,
+            ModuleItem::Stmt(Stmt::Decl(declaration)) if is_ambient => declaration,
+            _ => continue,
+        };
+
+        let declarations = match declaration {
+            Decl::Var(var) => get_var_declarations(var),
+            Decl::TsModule(namespace) if is_namespace(namespace) => {
+                let TsModuleName::Ident(ident) = &namespace.id else {
+                    continue;
+                };
+
+                declaration_tags.add(format!("{prefix}{}", ident.sym), position);
+
+                let nested_namespace = TsNamespaceDecl {
+                    span: namespace.span,
+                    declare: namespace.declare,
+                    global: namespace.global,
+                    id: ident.to_owned(),
+                    body: Box::new(namespace.body.to_owned().unwrap()),
+                };
+
+                add_nested_namespace_declarations(
+                    prefix,
+                    &nested_namespace,
+                    is_ambient || namespace.declare,
+                    member_declarations,
+                    declaration_tags,
+                )?;
+
+                continue;
+            }
+            Decl::TsModule(_) | Decl::Using(_) => continue,
+            declaration => vec![EntityDeclaration::from(declaration)?],
+        };
+
+        for declaration in declarations {
+            let name = format!("{prefix}{}", declaration.name());
+
+            declaration_tags.add(name.to_owned(), position);
+            add_declaration(name, declaration, member_declarations);
+        }
+    }
+
+    Ok(())
+}
+
+fn add_nested_namespace_declarations(
+    prefix: &str,
+    nested_namespace: &TsNamespaceDecl,
+    is_ambient: bool,
+    member_declarations: &mut Declarations,
+    declaration_tags: &mut DeclarationTags,
+) -> Result<()> {
+    let name = format!("{prefix}{}", nested_namespace.id.sym);
+
+    add_declaration(
+        name.to_owned(),
+        EntityDeclaration::Namespace(nested_namespace.id.sym.to_string()),
+        member_declarations,
+    );
+
+    add_namespace_member_declarations(
+        &format!("{name}."),
+        &nested_namespace.body,
+        is_ambient || nested_namespace.declare,
+        member_declarations,
+        declaration_tags,
+    )
+}
+
+fn add_import_bindings(import: &ImportDecl, imports: &mut Imports) {
+    let specifier = import.src.value.to_string();
+
+    for import_specifier in import.specifiers.iter() {
+        let (local_name, import_name) = match import_specifier {
+            ImportSpecifier::Named(named) => {
+                let import_name = named
+                    .imported
+                    .as_ref()
+                    .map(unwrap_module_export_name)
+                    .unwrap_or(named.local.sym.to_string());
+
+                (named.local.sym.to_string(), ImportName::Named(import_name))
+            }
+            ImportSpecifier::Default(default) => (
+                default.local.sym.to_string(),
+                ImportName::Named("default".into()),
+            ),
+            ImportSpecifier::Namespace(namespace) => {
+                (namespace.local.sym.to_string(), ImportName::Namespace)
+            }
+        };
+
+        imports.insert(local_name, (specifier.to_owned(), import_name));
+    }
+}
+
 fn get_default_export_declaration<'module>(
     default_export_declaration: &DefaultExportDeclaration<'module>,
     default_export_expression: &DefaultExportExpression<'module>,
-    mut declarations: Declarations,
+    export_assignment: &ExportAssignment<'module>,
+    declarations: &Declarations,
 ) -> Result<Option<EntityDeclaration>> {
     if let Some(export) = default_export_declaration {
         return Ok(Some(EntityDeclaration::from(&export.decl)?));
-    } else if default_export_expression.is_none() {
-        return Ok(None);
     }
 
-    let expression = default_export_expression.unwrap();
-    let export_identity = expression.expr.as_ident().unwrap();
-    let export_name = export_identity.sym.to_string();
+    // `export =` assigns `module.exports`, which is what default imports get.
+    let expression = match (default_export_expression, export_assignment) {
+        (Some(expression), _) => &expression.expr,
+        (None, Some(assignment)) => &assignment.expr,
+        (None, None) => return Ok(None),
+    };
+
+    // An identifier exports the entity declared under that name, if any.
+    if let Some(export_identity) = expression.unwrap_parens().as_ident() {
+        let export_name = export_identity.sym.to_string();
 
-    if let Some(declaration) = declarations.remove(&export_name) {
-        Ok(Some(declaration))
-    } else {
-        Ok(None)
+        if let Some(declaration) = declarations.get(&export_name) {
+            return Ok(Some(declaration.to_owned()));
+        }
+    }
+
+    match (default_export_expression, export_assignment) {
+        (Some(expression), _) => Ok(Some(EntityDeclaration::from(*expression)?)),
+        (None, Some(assignment)) => Ok(Some(EntityDeclaration::from(*assignment)?)),
+        (None, None) => Ok(None),
     }
 }
 
 fn get_named_export_declarations<'module>(
     declarations_with_export: &DeclarationsWithExport<'module>,
     exports_named: &ExportsNamed<'module>,
-    declarations: &mut Declarations,
-) -> Result<(Declarations, ExternalSpecifiers<'module>)> {
+    declarations: &Declarations,
+    imports: &Imports,
+    namespaces: &Namespaces<'module>,
+    is_ambient: bool,
+    declaration_tags: &mut DeclarationTags,
+) -> Result<(Declarations, Vec<IndirectExport>)> {
     let mut internal_exports: Declarations = Declarations::new();
-    let mut external_exports: ExternalSpecifiers = ExternalSpecifiers::new();
+    let mut indirect_exports: Vec<IndirectExport> = Vec::new();
+
+    // Local names of the exports that may be namespaces, with their exported names.
+    let mut exported_namespaces: Vec<(String, String)> = Vec::new();
 
     for export in declarations_with_export {
-        let export_declaration = EntityDeclaration::from(*export)?;
-        let export_name = export_declaration.name().to_owned();
+        let export_declarations = if let Some(var) = export.decl.as_var() {
+            get_var_declarations(var)
+        } else {
+            vec![EntityDeclaration::from(*export)?]
+        };
 
-        internal_exports.insert(export_name, export_declaration);
+        if export.decl.is_ts_module() {
+            for export_declaration in export_declarations.iter() {
+                let name = export_declaration.name().to_owned();
+
+                exported_namespaces.push((name.to_owned(), name));
+            }
+        }
+
+        for export_declaration in export_declarations {
+            let export_name = export_declaration.name().to_owned();
+
+            add_declaration(export_name, export_declaration, &mut internal_exports);
+        }
     }
 
+    // Type-only exports are kept, as types are part of the API of TypeScript entries and
+    // declaration files.
     for export in exports_named {
         let external_export_src = &export.src;
 
         for specifier in export.specifiers.iter() {
-            if let Some(import_path) = external_export_src {
-                let import_path = import_path.value.as_str();
-                let import_path = PathBuf::from(import_path);
-
-                if let Some(specifiers_for_import) = external_exports.get_mut(&import_path) {
-                    specifiers_for_import.push(specifier);
-                } else {
-                    external_exports.insert(import_path.to_owned(), vec![specifier]);
+            if let Some(import_specifier) = external_export_src {
+                let import_specifier = import_specifier.value.to_string();
+
+                if specifier.is_named() {
+                    let specifier = specifier.as_named().unwrap();
+                    let (actual_name, exported_name) = get_named_export_names(specifier);
+
+                    indirect_exports.push(IndirectExport {
+                        exported_name,
+                        specifier: import_specifier,
+                        import_name: ImportName::Named(actual_name),
+                    });
+                } else if specifier.is_namespace() {
+                    let specifier = specifier.as_namespace().unwrap();
+
+                    indirect_exports.push(IndirectExport {
+                        exported_name: unwrap_module_export_name(&specifier.name),
+                        specifier: import_specifier,
+                        import_name: ImportName::Namespace,
+                    });
                 }
 
                 continue;
@@ -215,14 +620,36 @@ fn get_named_export_declarations<'module>(
                 let specifier = specifier.as_named().unwrap();
                 let (actual_name, exported_name) = get_named_export_names(specifier);
 
-                if let Some(exported_declaration) = declarations.remove(&actual_name) {
-                    internal_exports.insert(exported_name, exported_declaration);
+                if let Some(exported_declaration) = declarations.get(&actual_name) {
+                    exported_namespaces.push((actual_name, exported_name.to_owned()));
+                    internal_exports.insert(exported_name, exported_declaration.to_owned());
+                } else if let Some((import_specifier, import_name)) = imports.get(&actual_name) {
+                    // Exporting an imported binding is the same as re-exporting it.
+                    indirect_exports.push(IndirectExport {
+                        exported_name,
+                        specifier: import_specifier.to_owned(),
+                        import_name: import_name.to_owned(),
+                    });
                 }
             }
         }
     }
 
-    Ok((internal_exports, external_exports))
+    // Namespace members are exported under the name of the namespace, like the members of
+    // re-exported module namespaces.
+    for (local_name, exported_name) in exported_namespaces {
+        for namespace in namespaces.get(&local_name).into_iter().flatten() {
+            add_namespace_member_declarations(
+                &format!("{exported_name}."),
+                namespace.body.as_ref().unwrap(),
+                is_ambient || namespace.declare,
+                &mut internal_exports,
+                declaration_tags,
+            )?;
+        }
+    }
+
+    Ok((internal_exports, indirect_exports))
 }
 
 fn unwrap_module_export_name(name: &ModuleExportName) -> String {
@@ -240,94 +667,331 @@ fn get_named_export_names(specifier: &ExportNamedSpecifier) -> (String, String)
     (actual_name, exported_name)
 }
 
-fn add_export_all_facade_exports(
-    base_import_path: &Path,
-    exports: ExportsFacadeAll,
-    buffer: &mut Declarations,
-) -> Result<()> {
-    for export in exports {
-        let import_file_path = export.src.value.to_string();
-        let import_file_path = base_import_path.join(import_file_path);
+fn get_commonjs_module_record(module: &Module) -> ModuleRecord {
+    let mut default_export = None;
+    let mut named_exports = Declarations::new();
 
-        let import_module = parse_import(&import_file_path).with_context(|| {
-            format!(
-                "Failed to parse imported module: {}",
-                import_file_path.display()
-            )
-        })?;
+    for item in module.body.iter() {
+        let expression = match item.as_stmt().and_then(|statement| statement.as_expr()) {
+            Some(statement) => &statement.expr,
+            None => continue,
+        };
+
+        if let Some(assignment) = expression.as_assign() {
+            add_commonjs_assignment_export(assignment, &mut default_export, &mut named_exports);
+        } else if let Some(call) = expression.as_call() {
+            // Object.defineProperty(exports, "name", { ... }) is emitted by most transpilers
+            // for re-exports and getters.
+            let callee = match &call.callee {
+                Callee::Expr(callee) => callee.as_member(),
+                _ => None,
+            };
+
+            let is_define_property = callee.is_some_and(|callee| {
+                is_ident_named(&callee.obj, "Object")
+                    && is_member_prop_named(&callee.prop, "defineProperty")
+            });
+
+            if !is_define_property || call.args.len().lt(&2) {
+                continue;
+            }
 
-        let import_module_dir = import_file_path.parent().unwrap().to_path_buf();
+            if !is_commonjs_exports_object(&call.args[0].expr) {
+                continue;
+            }
+
+            if let Some(Lit::Str(name)) = call.args[1].expr.as_lit() {
+                let name = name.value.to_string();
 
-        let (_, facade_named_exports) = get_exports_in_module(import_module_dir, import_module)
-            .with_context(|| {
-                format!(
-                    "Failed to get exports in module: {}",
-                    import_file_path.display()
-                )
-            })?;
+                if name.ne("__esModule") {
+                    let descriptor = call.args.get(2).map(|descriptor| descriptor.expr.as_ref());
+                    let declaration = get_commonjs_descriptor_entity(&name, descriptor);
 
-        buffer.extend(facade_named_exports);
+                    named_exports.insert(name, declaration);
+                }
+            }
+        }
     }
 
-    Ok(())
+    if let Some(default_export) = default_export {
+        named_exports.insert("default".into(), default_export);
+    }
+
+    ModuleRecord {
+        local_exports: named_exports,
+        ..Default::default()
+    }
 }
 
-fn add_named_facade_exports(
-    base_import_path: &Path,
-    exports: ExternalSpecifiers,
-    buffer: &mut Declarations,
-) -> Result<()> {
-    for (import_file_path, specifiers) in exports {
-        let import_file_path = base_import_path.join(import_file_path);
-
-        let import_module = parse_import(&import_file_path).with_context(|| {
-            format!(
-                "Failed to parse imported module: {}",
-                import_file_path.display()
-            )
-        })?;
-
-        let import_module_dir = import_file_path.parent().unwrap().to_path_buf();
-
-        let (default_facade_export, mut named_facade_exports) =
-            get_exports_in_module(import_module_dir, import_module).with_context(|| {
-                format!(
-                    "Failed to get exports in module: {}",
-                    import_file_path.display()
-                )
-            })?;
-
-        for specifiers in specifiers {
-            if specifiers.is_named() {
-                let specifier = specifiers.as_named().unwrap();
-                let (actual_name, exported_name) = get_named_export_names(specifier);
+fn add_commonjs_assignment_export(
+    assignment: &AssignExpr,
+    default_export: &mut Option<EntityDeclaration>,
+    named_exports: &mut Declarations,
+) {
+    let target = match assignment.left.as_simple() {
+        Some(SimpleAssignTarget::Member(target)) => target,
+        _ => return,
+    };
+
+    // module.exports = ...
+    if is_ident_named(&target.obj, "module") && is_member_prop_named(&target.prop, "exports") {
+        if let Some(object) = assignment.right.as_object() {
+            for property in object.props.iter() {
+                if let Some(name) = get_object_property_name(property) {
+                    let declaration = get_commonjs_property_entity(&name, property);
+                    named_exports.insert(name, declaration);
+                }
+            }
+        } else {
+            *default_export = Some(get_commonjs_entity("default", &assignment.right));
+        }
 
-                let exported_entity = if actual_name.eq("default") {
-                    default_facade_export.to_owned()
-                } else {
-                    named_facade_exports.remove(&actual_name)
-                };
+        return;
+    }
 
-                if exported_entity.is_some() {
-                    buffer.insert(exported_name, exported_entity.unwrap());
-                }
+    // exports.name = ... or module.exports.name = ...
+    if !is_commonjs_exports_object(&target.obj) {
+        return;
+    }
 
-                continue;
-            }
+    if let Some(name) = get_member_prop_name(target) {
+        let declaration = get_commonjs_entity(&name, &assignment.right);
 
-            if specifiers.is_namespace() {
-                let specifier = specifiers.as_namespace().unwrap();
-                let exported_name = unwrap_module_export_name(&specifier.name);
+        if name.eq("default") {
+            *default_export = Some(declaration);
+        } else if name.ne("__esModule") {
+            named_exports.insert(name, declaration);
+        }
+    }
+}
 
-                for (actual_name, declaration) in named_facade_exports.iter() {
-                    buffer.insert(
-                        format!("{}.{}", exported_name, actual_name),
-                        declaration.to_owned(),
-                    );
-                }
-            }
+fn get_commonjs_entity(name: &str, expression: &Expr) -> EntityDeclaration {
+    if let Some(function) = expression.as_fn_expr() {
+        return EntityDeclaration::Func(name.into(), function.function.to_owned());
+    } else if let Some(class) = expression.as_class() {
+        return EntityDeclaration::Class(name.into(), class.class.to_owned());
+    }
+
+    // Any other value is modeled as if it was assigned to a variable of the same name.
+    let declarator = VarDeclarator {
+        span: Default::default(),
+        name: Pat::Ident(Ident::new(name.into(), Default::default()).into()),
+        init: Some(Box::new(expression.to_owned())),
+        definite: false,
+    };
+
+    EntityDeclaration::Var(name.into(), declarator)
+}
+
+/// The entity of a property of an object assigned to `module.exports`.
+fn get_commonjs_property_entity(name: &str, property: &PropOrSpread) -> EntityDeclaration {
+    match property.as_prop().map(|property| property.as_ref()) {
+        Some(Prop::KeyValue(property)) => get_commonjs_entity(name, &property.value),
+        Some(Prop::Method(method)) => {
+            EntityDeclaration::Func(name.into(), method.function.to_owned())
+        }
+        // Shorthands refer to local bindings, and getters compute their value.
+        _ => get_commonjs_unknown_entity(name),
+    }
+}
+
+/// The entity of an `Object.defineProperty(exports, name, descriptor)` export, from the
+/// `value` of its descriptor. Getters compute their value, so it's unknown.
+fn get_commonjs_descriptor_entity(name: &str, descriptor: Option<&Expr>) -> EntityDeclaration {
+    let Some(descriptor) = descriptor.and_then(|descriptor| descriptor.unwrap_parens().as_object())
+    else {
+        return get_commonjs_unknown_entity(name);
+    };
+
+    for property in descriptor.props.iter() {
+        let Some(Prop::KeyValue(property)) = property.as_prop().map(|property| property.as_ref())
+        else {
+            continue;
+        };
+
+        let is_value = match &property.key {
+            PropName::Ident(ident) => ident.sym.eq("value"),
+            PropName::Str(key) => key.value.eq("value"),
+            _ => false,
+        };
+
+        if is_value {
+            return get_commonjs_entity(name, &property.value);
         }
     }
 
-    Ok(())
+    get_commonjs_unknown_entity(name)
+}
+
+/// An export whose value is computed, modeled as a variable without an initializer.
+fn get_commonjs_unknown_entity(name: &str) -> EntityDeclaration {
+    let declarator = VarDeclarator {
+        span: Default::default(),
+        name: Pat::Ident(Ident::new(name.into(), Default::default()).into()),
+        init: None,
+        definite: false,
+    };
+
+    EntityDeclaration::Var(name.into(), declarator)
+}
+
+fn is_commonjs_exports_object(expression: &Expr) -> bool {
+    if is_ident_named(expression, "exports") {
+        return true;
+    }
+
+    expression.as_member().is_some_and(|member| {
+        is_ident_named(&member.obj, "module") && is_member_prop_named(&member.prop, "exports")
+    })
+}
+
+fn is_ident_named(expression: &Expr, name: &str) -> bool {
+    expression
+        .as_ident()
+        .is_some_and(|ident| ident.sym.as_str().eq(name))
+}
+
+fn is_member_prop_named(prop: &MemberProp, name: &str) -> bool {
+    prop.as_ident()
+        .is_some_and(|ident| ident.sym.as_str().eq(name))
+}
+
+fn get_member_prop_name(member: &MemberExpr) -> Option<String> {
+    match &member.prop {
+        MemberProp::Ident(ident) => Some(ident.sym.to_string()),
+        MemberProp::Computed(computed) => match computed.expr.as_lit() {
+            Some(Lit::Str(name)) => Some(name.value.to_string()),
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecma::docs::ReleaseTag;
+    use crate::ecma::entity::EntityKind;
+    use crate::ecma::parser::parse_module_source;
+
+    fn get_local_exports(source: &str) -> Declarations {
+        parse_module_record("index.js", source).local_exports
+    }
+
+    fn parse_module_record(module_path: &str, source: &str) -> ModuleRecord {
+        let module_path = Path::new(module_path);
+        let (module, comments) = parse_module_source(module_path, source.into()).unwrap();
+
+        get_module_record(module_path, &module, &comments).unwrap()
+    }
+
+    /// The source of the literal a variable export is initialized with.
+    fn get_literal_value(declaration: &EntityDeclaration) -> Option<String> {
+        let EntityDeclaration::Var(_, declarator) = declaration else {
+            return None;
+        };
+
+        match declarator.init.as_ref()?.as_lit()? {
+            Lit::Str(string) => Some(format!("{:?}", string.value.as_ref())),
+            Lit::Num(number) => Some(number.value.to_string()),
+            _ => None,
+        }
+    }
+
+    fn is_function(declaration: &EntityDeclaration) -> bool {
+        matches!(declaration, EntityDeclaration::Func(..))
+    }
+
+    fn is_unknown(declaration: &EntityDeclaration) -> bool {
+        matches!(declaration, EntityDeclaration::Var(_, declarator) if declarator.init.is_none())
+    }
+
+    #[test]
+    fn commonjs_object_properties_have_their_own_values() {
+        let exports = get_local_exports(
+            r#"module.exports = { VERSION: "1.0", foo: function () {}, bar() {}, Baz: class {}, qux };"#,
+        );
+
+        assert_eq!(
+            get_literal_value(&exports["VERSION"]),
+            Some("\"1.0\"".into())
+        );
+        assert!(is_function(&exports["foo"]));
+        assert!(is_function(&exports["bar"]));
+        assert!(matches!(exports["Baz"], EntityDeclaration::Class(..)));
+        assert!(is_unknown(&exports["qux"]));
+    }
+
+    #[test]
+    fn commonjs_define_property_uses_the_descriptor_value() {
+        let exports = get_local_exports(
+            r#"
+            Object.defineProperty(exports, "__esModule", { value: true });
+            Object.defineProperty(exports, "answer", { enumerable: true, value: 42 });
+            Object.defineProperty(exports, "run", { value: function () {} });
+            Object.defineProperty(exports, "foo", { enumerable: true, get: function () { return a; } });
+            Object.defineProperty(exports, "bar");
+            "#,
+        );
+
+        assert!(!exports.contains_key("__esModule"));
+        assert_eq!(get_literal_value(&exports["answer"]), Some("42".into()));
+        assert!(is_function(&exports["run"]));
+        assert!(is_unknown(&exports["foo"]));
+        assert!(is_unknown(&exports["bar"]));
+    }
+
+    #[test]
+    fn commonjs_assignments_have_their_own_values() {
+        let exports = get_local_exports(r#"exports.a = "x"; module.exports.b = function () {};"#);
+
+        assert_eq!(get_literal_value(&exports["a"]), Some("\"x\"".into()));
+        assert!(is_function(&exports["b"]));
+    }
+
+    #[test]
+    fn enums_are_only_declared_outside_of_declaration_files() {
+        let source = "export declare enum A { X } export declare const enum B { X } export const enum C { X }";
+        let exports = parse_module_record("index.ts", source).local_exports;
+
+        assert_eq!(exports["A"].kind(), EntityKind::DeclaredEnum);
+        assert_eq!(exports["B"].kind(), EntityKind::DeclaredConstEnum);
+        assert_eq!(exports["C"].kind(), EntityKind::ConstEnum);
+
+        let exports = parse_module_record("index.d.ts", source).local_exports;
+
+        assert_eq!(exports["A"].kind(), EntityKind::Enum);
+        assert_eq!(exports["B"].kind(), EntityKind::ConstEnum);
+        assert_eq!(exports["C"].kind(), EntityKind::ConstEnum);
+    }
+
+    #[test]
+    fn namespace_members_inherit_the_tags_of_their_namespaces() {
+        let doc_tags = parse_module_record(
+            "index.ts",
+            r#"
+            /** @beta */
+            export namespace Outer {
+                export const a = 1;
+                /** @public */
+                export const b = 2;
+                /** @deprecated */
+                export namespace Inner {
+                    export const c = 3;
+                }
+            }
+            export const d = 4;
+            "#,
+        )
+        .doc_tags;
+
+        let beta = Some(ReleaseTag::Beta);
+
+        assert_eq!(doc_tags["Outer.a"].release_tag, beta);
+        assert_eq!(doc_tags["Outer.b"].release_tag, Some(ReleaseTag::Public));
+        assert_eq!(doc_tags["Outer.Inner.c"].release_tag, beta);
+        assert!(doc_tags["Outer.Inner.c"].is_deprecated);
+        assert!(!doc_tags["Outer.a"].is_deprecated);
+        assert!(!doc_tags.contains_key("d"));
+    }
 }
@@ -0,0 +1,504 @@
+use anyhow::{bail, Result};
+use std::path::Path;
+
+// See https://git-scm.com/docs/gitignore#_pattern_format for the semantics reproduced
+// here, and git's wildmatch.c for the matching of the patterns themselves.
+
+pub struct IgnoreRule {
+    /// The rule as written, e.g. `!/dist/**`.
+    pub source: String,
+    pub is_negated: bool,
+    is_dir_only: bool,
+    is_anchored: bool,
+    pattern: Vec<char>,
+}
+
+/// A set of ignore rules, such as those in a `.npmignore` file, matched against paths
+/// relative to the directory the rules were defined in.
+#[derive(Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRule {
+    pub fn new(line: &str) -> Result<Option<Self>> {
+        let line = trim_unescaped_trailing_spaces(line.trim_end_matches('\r'));
+
+        // Escaped leading characters ("\#", "\!") are kept, and matched literally.
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let (is_negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line),
+        };
+
+        let (is_dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+
+        if pattern.is_empty() {
+            bail!("Invalid ignore rule: {line}");
+        }
+
+        // Patterns with a separator at the beginning or in the middle are relative to the
+        // directory the rule was defined in, others match at any depth.
+        let is_anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+
+        Ok(Some(Self {
+            source: line.to_string(),
+            is_negated,
+            is_dir_only,
+            is_anchored,
+            pattern: pattern.chars().collect(),
+        }))
+    }
+
+    pub fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.is_dir_only && !is_dir {
+            return false;
+        }
+
+        let path = path.to_string_lossy();
+
+        let text: Vec<char> = if self.is_anchored {
+            path.chars().collect()
+        } else {
+            match path.rsplit('/').next() {
+                Some(file_name) => file_name.chars().collect(),
+                None => return false,
+            }
+        };
+
+        wildmatch(&self.pattern, &text, true)
+    }
+
+    /// Whether the rule could match a path inside the given directory, which is used to
+    /// keep walking excluded directories that contain re-included paths.
+    pub fn may_match_within(&self, dir: &Path) -> bool {
+        if !self.is_anchored {
+            return true;
+        }
+
+        let segments: Vec<&[char]> = self.pattern.split(|char| char.eq(&'/')).collect();
+
+        for (index, component) in dir.components().enumerate() {
+            let segment = match segments.get(index) {
+                Some(segment) => *segment,
+                None => return false,
+            };
+
+            if segment.eq(&['*', '*']) {
+                return true;
+            }
+
+            let component: Vec<char> = component.as_os_str().to_string_lossy().chars().collect();
+
+            if !wildmatch(segment, &component, true) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl IgnoreRules {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+
+        for line in source.lines() {
+            if let Some(rule) = IgnoreRule::new(line)? {
+                rules.push(rule);
+            }
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn push(&mut self, line: &str) -> Result<()> {
+        if let Some(rule) = IgnoreRule::new(line)? {
+            self.rules.push(rule);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the last rule matching the path, if any. The path is ignored when the
+    /// rule is not negated.
+    pub fn get_matching_rule(&self, path: &Path, is_dir: bool) -> Option<&IgnoreRule> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_dir))
+    }
+
+    pub fn may_include_within(&self, dir: &Path) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.is_negated && rule.may_match_within(dir))
+    }
+}
+
+fn trim_unescaped_trailing_spaces(line: &str) -> &str {
+    let mut line = line;
+
+    while let Some(trimmed_line) = line.strip_suffix(' ') {
+        if trimmed_line.ends_with('\\') {
+            break;
+        }
+
+        line = trimmed_line;
+    }
+
+    line
+}
+
+/// Matches a path against a pattern. Wildcards do not match separators, unless they are
+/// a `**` spanning a whole segment of the pattern.
+fn wildmatch(pattern: &[char], text: &[char], is_segment_start: bool) -> bool {
+    let (pattern_char, pattern_rest) = match pattern.split_first() {
+        Some(split) => split,
+        None => return text.is_empty(),
+    };
+
+    match pattern_char {
+        '\\' => match (pattern_rest.split_first(), text.split_first()) {
+            (Some((escaped_char, pattern_rest)), Some((text_char, text_rest))) => {
+                escaped_char.eq(text_char) && wildmatch(pattern_rest, text_rest, false)
+            }
+            // A trailing backslash is invalid and never matches.
+            _ => false,
+        },
+        '?' => match text.split_first() {
+            Some((text_char, text_rest)) => {
+                text_char.ne(&'/') && wildmatch(pattern_rest, text_rest, false)
+            }
+            None => false,
+        },
+        '*' => {
+            let pattern_rest = trim_leading_stars(pattern_rest);
+            let is_double_star = pattern.len().saturating_sub(pattern_rest.len()).gt(&1);
+
+            if is_double_star && is_segment_start {
+                match pattern_rest.split_first() {
+                    // A trailing "**" matches everything.
+                    None => return true,
+                    // A "**/" matches zero or more directories.
+                    Some(('/', pattern_rest)) => {
+                        return wildmatch(pattern_rest, text, true)
+                            || text.iter().enumerate().any(|(index, text_char)| {
+                                text_char.eq(&'/')
+                                    && wildmatch(pattern_rest, &text[index + 1..], true)
+                            });
+                    }
+                    // Otherwise, consecutive stars act as a single one.
+                    Some(_) => {}
+                }
+            }
+
+            for index in 0..=text.len() {
+                if wildmatch(pattern_rest, &text[index..], false) {
+                    return true;
+                }
+
+                if text.get(index).is_some_and(|text_char| text_char.eq(&'/')) {
+                    break;
+                }
+            }
+
+            false
+        }
+        '[' => match (
+            match_char_class(pattern_rest, text.first()),
+            text.split_first(),
+        ) {
+            (Some((is_match, pattern_rest)), Some((_, text_rest))) => {
+                is_match && wildmatch(pattern_rest, text_rest, false)
+            }
+            (Some(_), None) => false,
+            // An unterminated bracket expression matches a literal bracket.
+            (None, Some((text_char, text_rest))) => {
+                text_char.eq(&'[') && wildmatch(pattern_rest, text_rest, false)
+            }
+            (None, None) => false,
+        },
+        _ => match text.split_first() {
+            Some((text_char, text_rest)) => {
+                pattern_char.eq(text_char) && wildmatch(pattern_rest, text_rest, text_char.eq(&'/'))
+            }
+            None => false,
+        },
+    }
+}
+
+fn trim_leading_stars(pattern: &[char]) -> &[char] {
+    let star_count = pattern.iter().take_while(|char| char.eq(&&'*')).count();
+
+    &pattern[star_count..]
+}
+
+/// Matches a character against a bracket expression, whose opening bracket has already
+/// been consumed. Returns whether it matched along with the rest of the pattern, or
+/// `None` when the expression is not terminated.
+fn match_char_class<'pattern>(
+    pattern: &'pattern [char],
+    text_char: Option<&char>,
+) -> Option<(bool, &'pattern [char])> {
+    let (is_negated, mut pattern) = match pattern.split_first() {
+        Some(('!' | '^', pattern)) => (true, pattern),
+        _ => (false, pattern),
+    };
+
+    let text_char = text_char.copied().unwrap_or_default();
+    let mut is_match = false;
+    let mut is_first = true;
+
+    loop {
+        let (class_char, pattern_rest) = pattern.split_first()?;
+
+        match class_char {
+            // A closing bracket right after the opening one is a literal.
+            ']' if !is_first => {
+                // Bracket expressions never match a separator, even when negated.
+                let is_match = is_match.ne(&is_negated) && text_char.ne(&'/');
+
+                return Some((is_match, pattern_rest));
+            }
+            '[' if pattern_rest.first().is_some_and(|char| char.eq(&':')) => {
+                let class_end = pattern_rest
+                    .windows(2)
+                    .position(|chars| chars.eq(&[':', ']']))?;
+                let class_name: String = pattern_rest[1..class_end].iter().collect();
+
+                is_match |= match class_name.as_str() {
+                    "alnum" => text_char.is_ascii_alphanumeric(),
+                    "alpha" => text_char.is_ascii_alphabetic(),
+                    "blank" => text_char.eq(&' ') || text_char.eq(&'\t'),
+                    "cntrl" => text_char.is_ascii_control(),
+                    "digit" => text_char.is_ascii_digit(),
+                    "graph" => text_char.is_ascii_graphic(),
+                    "lower" => text_char.is_ascii_lowercase(),
+                    "print" => text_char.is_ascii_graphic() || text_char.eq(&' '),
+                    "punct" => text_char.is_ascii_punctuation(),
+                    "space" => text_char.is_ascii_whitespace(),
+                    "upper" => text_char.is_ascii_uppercase(),
+                    "xdigit" => text_char.is_ascii_hexdigit(),
+                    // Like git, an unknown class makes the whole pattern fail to match.
+                    _ => return Some((false, &[])),
+                };
+
+                pattern = &pattern_rest[class_end + 2..];
+            }
+            _ => {
+                let (range_start, pattern_rest) = unescape_class_char(class_char, pattern_rest)?;
+
+                match pattern_rest {
+                    ['-', range_end, pattern_rest @ ..] if range_end.ne(&']') => {
+                        let (range_end, pattern_rest) =
+                            unescape_class_char(range_end, pattern_rest)?;
+
+                        is_match |= (range_start..=range_end).contains(&text_char);
+                        pattern = pattern_rest;
+                    }
+                    _ => {
+                        is_match |= range_start.eq(&text_char);
+                        pattern = pattern_rest;
+                    }
+                }
+            }
+        }
+
+        is_first = false;
+    }
+}
+
+fn unescape_class_char<'pattern>(
+    class_char: &char,
+    pattern_rest: &'pattern [char],
+) -> Option<(char, &'pattern [char])> {
+    match class_char {
+        '\\' => pattern_rest
+            .split_first()
+            .map(|(escaped_char, pattern_rest)| (*escaped_char, pattern_rest)),
+        _ => Some((*class_char, pattern_rest)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Examples from https://git-scm.com/docs/gitignore, evaluated the way git does: a
+    // path is also ignored when one of its parent directories is.
+    fn is_ignored(rules: &str, path: &str, is_dir: bool) -> bool {
+        let rules = IgnoreRules::parse(rules).unwrap();
+        let path = Path::new(path);
+
+        for ancestor in path.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+
+            if let Some(rule) = rules.get_matching_rule(ancestor, true) {
+                if !rule.is_negated {
+                    return true;
+                }
+            }
+        }
+
+        rules
+            .get_matching_rule(path, is_dir)
+            .is_some_and(|rule| !rule.is_negated)
+    }
+
+    #[test]
+    fn blank_lines_and_comments_match_nothing() {
+        let rules = IgnoreRules::parse("\n# comment\n   \n").unwrap();
+
+        assert!(rules.rules.is_empty());
+    }
+
+    #[test]
+    fn escaped_hash_matches_literally() {
+        assert!(is_ignored("\\#file", "#file", false));
+        assert!(!is_ignored("\\#file", "file", false));
+    }
+
+    #[test]
+    fn trailing_spaces_are_ignored_unless_escaped() {
+        assert!(is_ignored("foo   ", "foo", false));
+        assert!(!is_ignored("foo\\ ", "foo", false));
+        assert!(is_ignored("foo\\ ", "foo ", false));
+    }
+
+    #[test]
+    fn negation_re_includes_a_file() {
+        let rules = "*.html\n!foo.html";
+
+        assert!(is_ignored(rules, "bar.html", false));
+        assert!(!is_ignored(rules, "foo.html", false));
+    }
+
+    #[test]
+    fn escaped_exclamation_mark_matches_literally() {
+        assert!(is_ignored("\\!important!.txt", "!important!.txt", false));
+        assert!(!is_ignored("\\!important!.txt", "important!.txt", false));
+    }
+
+    #[test]
+    fn negation_cannot_re_include_a_file_of_an_excluded_directory() {
+        assert!(is_ignored("foo/\n!foo/bar.txt", "foo/bar.txt", false));
+    }
+
+    #[test]
+    fn trailing_slash_only_matches_directories() {
+        assert!(is_ignored("foo/", "foo", true));
+        assert!(is_ignored("foo/", "foo/bar/hello.c", false));
+        assert!(!is_ignored("foo/", "foo", false));
+    }
+
+    #[test]
+    fn pattern_without_slash_matches_at_any_level() {
+        assert!(is_ignored("frotz/", "frotz", true));
+        assert!(is_ignored("frotz/", "a/frotz", true));
+        assert!(is_ignored("hello.*", "hello.txt", false));
+        assert!(is_ignored("hello.*", "a/hello.java", false));
+    }
+
+    #[test]
+    fn pattern_with_middle_slash_is_anchored() {
+        assert!(is_ignored("doc/frotz/", "doc/frotz", true));
+        assert!(!is_ignored("doc/frotz/", "a/doc/frotz", true));
+    }
+
+    #[test]
+    fn leading_slash_is_irrelevant_with_a_middle_slash() {
+        for rules in ["doc/frotz", "/doc/frotz"] {
+            assert!(is_ignored(rules, "doc/frotz", false));
+            assert!(!is_ignored(rules, "a/doc/frotz", false));
+        }
+    }
+
+    #[test]
+    fn leading_slash_anchors_the_pattern() {
+        assert!(is_ignored("/hello.*", "hello.txt", false));
+        assert!(is_ignored("/hello.*", "hello.c", false));
+        assert!(!is_ignored("/hello.*", "a/hello.java", false));
+    }
+
+    #[test]
+    fn star_does_not_match_a_slash() {
+        assert!(is_ignored("foo/*", "foo/test.json", false));
+        assert!(is_ignored("foo/*", "foo/bar", true));
+
+        // The file is still ignored through its parent directory.
+        let rule = IgnoreRule::new("foo/*").unwrap().unwrap();
+
+        assert!(!rule.matches(Path::new("foo/bar/hello.c"), false));
+    }
+
+    #[test]
+    fn question_mark_matches_one_character_except_a_slash() {
+        assert!(is_ignored("fo?", "foo", false));
+        assert!(!is_ignored("fo?", "fooo", false));
+        assert!(!is_ignored("a?b", "a/b", false));
+    }
+
+    #[test]
+    fn range_notation_matches_one_character_of_the_range() {
+        assert!(is_ignored("[a-zA-Z].txt", "b.txt", false));
+        assert!(is_ignored("[a-zA-Z].txt", "Q.txt", false));
+        assert!(!is_ignored("[a-zA-Z].txt", "1.txt", false));
+        assert!(is_ignored("[!a-z].txt", "1.txt", false));
+        assert!(is_ignored("[[:digit:]].txt", "1.txt", false));
+        assert!(is_ignored("[]].txt", "].txt", false));
+        assert!(!is_ignored("a[!b]c", "a/c", false));
+    }
+
+    #[test]
+    fn leading_double_star_matches_in_all_directories() {
+        assert!(is_ignored("**/foo", "foo", false));
+        assert!(is_ignored("**/foo", "a/b/foo", false));
+        assert!(is_ignored("**/foo/bar", "foo/bar", false));
+        assert!(is_ignored("**/foo/bar", "a/foo/bar", false));
+        assert!(!is_ignored("**/foo/bar", "a/foo/baz/bar", false));
+    }
+
+    #[test]
+    fn trailing_double_star_matches_everything_inside() {
+        assert!(is_ignored("abc/**", "abc/file", false));
+        assert!(is_ignored("abc/**", "abc/x/y/file", false));
+        assert!(!is_ignored("abc/**", "abc", true));
+    }
+
+    #[test]
+    fn middle_double_star_matches_zero_or_more_directories() {
+        for path in ["a/b", "a/x/b", "a/x/y/b"] {
+            assert!(is_ignored("a/**/b", path, false));
+        }
+
+        assert!(!is_ignored("a/**/b", "a/xb", false));
+    }
+
+    #[test]
+    fn other_consecutive_stars_are_regular_stars() {
+        assert!(is_ignored("foo**bar", "fooxbar", false));
+        assert!(!is_ignored("/foo**/bar", "foo/x/bar", false));
+        assert!(is_ignored("/foo**/bar", "fooxx/bar", false));
+    }
+
+    #[test]
+    fn everything_except_a_specific_directory() {
+        let rules = "/*\n!/foo\n/foo/*\n!/foo/bar";
+
+        assert!(is_ignored(rules, "baz", false));
+        assert!(!is_ignored(rules, "foo", true));
+        assert!(is_ignored(rules, "foo/baz", false));
+        assert!(!is_ignored(rules, "foo/bar", true));
+        assert!(!is_ignored(rules, "foo/bar/hello.c", false));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod cli;
 pub mod diff;
 mod ecma;
 mod fs;
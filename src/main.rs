@@ -1,34 +1,115 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
+use breakpoint::cli::{parse_args, Command};
 use breakpoint::diff::analyzer;
+use breakpoint::diff::consistency;
+use breakpoint::diff::options::DiffOptions;
 use breakpoint::diff::printer;
 use breakpoint::pkg::registry;
+use breakpoint::pkg::Pkg;
 use std::env;
+use std::panic;
 use std::path::PathBuf;
 use std::process::ExitCode;
+use std::thread;
 use std::time::Instant;
 
 fn main() -> Result<ExitCode> {
     let start = Instant::now();
     let args: Vec<String> = env::args().collect();
 
-    if args.len().ne(&2) {
-        bail!("Expected only one argument: the package path.");
+    match parse_args(&args)? {
+        Command::Diff { pkg_dir, options } => run_diff(pkg_dir, options, start),
+        Command::Check { pkg_dir, jobs } => run_check(pkg_dir, jobs, start),
+        Command::PackList {
+            pkg_dir,
+            compare_published,
+        } => run_pack_list(pkg_dir, compare_published, start),
     }
+}
+
+fn run_diff(working_dir: PathBuf, mut options: DiffOptions, start: Instant) -> Result<ExitCode> {
+    let pkg_config = Pkg::parse_config_in_dir(&working_dir)
+        .with_context(|| "Failed to load package.json config.")?;
+
+    options
+        .apply_pkg_config(&pkg_config)
+        .with_context(|| "Failed to apply breakpoint options from package.json config.")?;
 
-    let working_dir = args.last().unwrap();
-    let working_dir = PathBuf::from(working_dir);
+    // The previous package is fetched from the registry while the current one is loaded.
+    let (pkg_current, pkg_previous) = thread::scope(|scope| {
+        let pkg_previous = scope.spawn(|| {
+            registry::fetch_from_server(&working_dir, &options.tarball_limits)
+                .with_context(|| "Failed to fetch previous package from registry server.")
+        });
 
-    let pkg_current = registry::load_from_dir(working_dir)
-        .with_context(|| "Failed to load current package from file system.")?;
+        let pkg_current = registry::load_from_dir(working_dir.to_owned())
+            .with_context(|| "Failed to load current package from file system.");
 
-    let pkg_previous = registry::fetch_from_server(&pkg_current)
-        .with_context(|| "Failed to fetch previous package from registry server.")?;
+        let pkg_previous = pkg_previous
+            .join()
+            .unwrap_or_else(|panic| panic::resume_unwind(panic));
 
-    let diff_results = analyzer::get_diff_between(pkg_previous, pkg_current)
+        (pkg_current, pkg_previous)
+    });
+
+    let pkg_current = pkg_current?;
+    let pkg_previous = pkg_previous?;
+
+    let diff_results = analyzer::get_diff_between(pkg_previous, pkg_current, &options)
         .with_context(|| "Breaking diff analysis between previous & current versions failed.")?;
 
     printer::print_asset_issues(&diff_results);
     printer::print_entry_issues(&diff_results);
+    printer::print_export_cycles(&diff_results.export_cycles);
+    printer::print_types_issues(&diff_results);
 
     Ok(printer::print_exit(&diff_results, start))
 }
+
+fn run_check(working_dir: PathBuf, jobs: usize, start: Instant) -> Result<ExitCode> {
+    let pkg = registry::load_from_dir(working_dir)
+        .with_context(|| "Failed to load package from file system.")?;
+
+    let consistency_results = consistency::get_consistency_of(&pkg, jobs)
+        .with_context(|| "Consistency analysis between package entries failed.")?;
+
+    printer::print_consistency_warnings(&consistency_results);
+    printer::print_export_cycles(&consistency_results.export_cycles);
+
+    Ok(printer::print_consistency_exit(&consistency_results, start))
+}
+
+fn run_pack_list(
+    working_dir: PathBuf,
+    compare_published: bool,
+    start: Instant,
+) -> Result<ExitCode> {
+    let pkg = registry::load_from_dir(working_dir)
+        .with_context(|| "Failed to load package from file system.")?;
+
+    printer::print_pack_list(pkg.contents.pack_list_paths());
+
+    if compare_published {
+        let pkg_config = Pkg::parse_config_in_dir(&pkg.dir)
+            .with_context(|| "Failed to load package.json config.")?;
+
+        let mut options = DiffOptions::default();
+
+        options
+            .apply_pkg_config(&pkg_config)
+            .with_context(|| "Failed to apply breakpoint options from package.json config.")?;
+
+        let pkg_published = registry::fetch_from_server(&pkg.dir, &options.tarball_limits)
+            .with_context(|| "Failed to fetch previous package from registry server.")?;
+
+        let pack_list_diff_results = analyzer::get_pack_list_diff_between(&pkg_published, &pkg)
+            .with_context(|| "Failed to compare files with the last published version.")?;
+
+        printer::print_pack_list_diff(&pack_list_diff_results);
+    }
+
+    Ok(printer::print_pack_list_exit(
+        pkg.contents.pack_list_paths(),
+        start,
+    ))
+}
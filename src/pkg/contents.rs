@@ -1,22 +1,20 @@
+use crate::ecma::graph::ModuleCache;
 use crate::fs::file::FileExt;
-use crate::fs::path::get_matching_files_in_dir;
+use crate::pkg::packlist::{get_pack_list_paths, PackListPath};
 use crate::pkg::tarball::PkgTarball;
 use anyhow::{Context, Result};
-use glob::Pattern;
-use json::iterators::Members;
 use json::JsonValue;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use strum::IntoEnumIterator;
-use tar::Entry;
+use std::path::{Path, PathBuf};
 
 pub struct PkgContents {
     pub pkg_dir: PathBuf,
-    pub include_patterns: Vec<Pattern>,
-    pub exclude_patterns: Vec<Pattern>,
-    pub exclude_negation_patterns: Vec<Pattern>,
+    /// Files that would be published from the package directory, when not a tarball.
+    pack_list: BTreeSet<PathBuf>,
+    pack_list_paths: Vec<PackListPath>,
     pkg_tarball: Option<PkgTarball>,
+    module_cache: ModuleCache,
 }
 
 impl PkgContents {
@@ -25,31 +23,26 @@ impl PkgContents {
         pkg_json: &JsonValue,
         pkg_tarball: Option<PkgTarball>,
     ) -> Result<Self> {
-        let mut contents = Self {
-            pkg_dir,
-            pkg_tarball,
-            include_patterns: Vec::new(),
-            exclude_patterns: Vec::new(),
-            exclude_negation_patterns: Vec::new(),
+        let pack_list_paths = if pkg_tarball.is_none() {
+            get_pack_list_paths(&pkg_dir, pkg_json)
+                .with_context(|| "Failed to determine the files to publish in package.")?
+        } else {
+            Vec::new()
         };
 
-        if contents.pkg_tarball.is_none() {
-            let include_globs = pkg_json["files"].members();
-
-            let include_patterns = contents
-                .get_file_include_patterns(include_globs)
-                .with_context(|| "Failed to gather package file include patterns.")?;
-
-            let (exclude_patterns, exclude_negation_patterns) = contents
-                .get_file_exclude_patterns(&include_patterns)
-                .with_context(|| "Failed to gather package file exclude patterns.")?;
+        let pack_list = pack_list_paths
+            .iter()
+            .filter(|pack_list_path| pack_list_path.is_included)
+            .map(|pack_list_path| pack_list_path.path.to_owned())
+            .collect();
 
-            contents.include_patterns = include_patterns;
-            contents.exclude_patterns = exclude_patterns;
-            contents.exclude_negation_patterns = exclude_negation_patterns;
-        }
-
-        Ok(contents)
+        Ok(Self {
+            pkg_dir,
+            pack_list,
+            pack_list_paths,
+            pkg_tarball,
+            module_cache: ModuleCache::default(),
+        })
     }
 
     pub fn is_tarball(&self) -> bool {
@@ -57,51 +50,42 @@ impl PkgContents {
     }
 
     pub fn asset_list(&self) -> Result<HashSet<PathBuf>> {
+        Ok(self
+            .file_list()?
+            .into_iter()
+            .filter(|file_path| FileExt::from(file_path).is_other())
+            .collect())
+    }
+
+    /// Lists the included and excluded paths of a local package, with the reason for each.
+    pub fn pack_list_paths(&self) -> &[PackListPath] {
+        &self.pack_list_paths
+    }
+
+    /// Lists all files in the package, relative to the package root.
+    pub fn file_list(&self) -> Result<HashSet<PathBuf>> {
         if self.is_tarball() {
             let tarball = self.pkg_tarball.as_ref().unwrap();
-            let tarball_files = tarball
-                .get_files(Some(|entry: &Entry<&[u8]>| {
-                    let entry_path = &entry.path().unwrap().to_path_buf();
-                    let entry_ext = FileExt::from(entry_path);
 
-                    Ok(entry_ext.is_other())
-                }))
-                .with_context(|| "Failed to get package tarball files.")?;
-
-            return Ok(tarball_files);
+            return Ok(tarball.get_files());
         }
 
-        let mut matched_files = HashSet::new();
-        let mut exclude_patterns = self.exclude_patterns.to_owned();
-
-        for ext in FileExt::iter() {
-            if !ext.is_other() {
-                let glob_path = format!("**/*.{}", ext.to_value());
-                let glob_path = self.pkg_dir.join(glob_path);
+        Ok(self.pack_list.iter().cloned().collect())
+    }
 
-                let glob_pattern =
-                    Pattern::new(glob_path.to_str().unwrap()).with_context(|| {
-                        format!(
-                            "Invalid FileExt exclusion glob pattern: {}",
-                            glob_path.display()
-                        )
-                    })?;
+    /// Whether the package includes the file at the given path, relative to its root.
+    pub fn has_file(&self, file_path: &Path) -> Result<bool> {
+        let file_path = file_path.strip_prefix("./").unwrap_or(file_path);
 
-                exclude_patterns.push(glob_pattern);
-            }
+        if let Some(tarball) = &self.pkg_tarball {
+            return Ok(tarball.get_file(file_path).is_some());
         }
 
-        get_matching_files_in_dir(
-            &self.pkg_dir.to_path_buf(),
-            &mut matched_files,
-            &self.include_patterns,
-            &exclude_patterns,
-            &self.exclude_negation_patterns,
-            &|entry_path| Ok(entry_path.strip_prefix(&self.pkg_dir)?.to_path_buf()),
-        )
-        .with_context(|| "Failed to get package directory files.")?;
+        Ok(self.pack_list.contains(file_path))
+    }
 
-        Ok(matched_files)
+    pub(crate) fn module_cache(&self) -> &ModuleCache {
+        &self.module_cache
     }
 
     pub fn load_file(&self, file_path: &PathBuf) -> Result<Option<Vec<u8>>> {
@@ -127,172 +111,4 @@ impl PkgContents {
             },
         )?))
     }
-
-    fn get_file_include_patterns(&self, include_globs: Members) -> Result<Vec<Pattern>> {
-        if include_globs.len().eq(&0) {
-            let glob_path = self.pkg_dir.join("**/*");
-            let glob_path = glob_path.to_str().unwrap();
-
-            let glob_pattern = Pattern::new(glob_path)
-                .with_context(|| format!("Invalid 'all files' glob pattern: {glob_path}"))?;
-
-            return Ok(vec![glob_pattern]);
-        }
-
-        let mut patterns: Vec<Pattern> = Vec::with_capacity(include_globs.len());
-
-        for glob_path in include_globs {
-            let glob_path = glob_path.to_string();
-
-            // We filter out paths that go outside the package root — npm considers them invalid.
-            if glob_path.starts_with("../") {
-                continue;
-            }
-
-            let glob_path = self.pkg_dir.join(glob_path);
-            let glob_path = glob_path.to_str().unwrap();
-
-            let glob_pattern = Pattern::new(glob_path)
-                .with_context(|| format!("Invalid 'files' glob pattern: {glob_path}"))?;
-
-            patterns.push(glob_pattern)
-        }
-
-        Ok(patterns)
-    }
-
-    // See https://docs.npmjs.com/cli/v10/configuring-npm/package-json#files
-    fn get_file_exclude_patterns(
-        &self,
-        include_patterns: &Vec<Pattern>,
-    ) -> Result<(Vec<Pattern>, Vec<Pattern>)> {
-        let mut exclude_patterns: HashSet<Pattern> = HashSet::new();
-        let mut exclude_negation_patterns: HashSet<Pattern> = HashSet::new();
-
-        // These files are always excluded, regardless of settings.
-        let hard_exclude_globs = vec![
-            "**/.git",
-            "**/.npmrc",
-            "**/node_modules",
-            "**/package-lock.json",
-            "**/pnpm-lock.yaml",
-            "**/yarn.lock",
-        ];
-
-        for glob in hard_exclude_globs {
-            exclude_patterns.insert(
-                self.to_pkg_file_pattern(glob)
-                    .with_context(|| "Failed to create pkg file hard exclude glob pattern.")?,
-            );
-        }
-
-        // These files are excluded by default, but can be configured to be included.
-        let soft_exclude_globs = vec![
-            "**/*.orig",
-            "**/.*.swp",
-            "**/.DS_Store",
-            "**/._*",
-            "**/.hg",
-            "**/.lock-wscript",
-            "**/.svn",
-            "**/.wafpickle-N",
-            "**/CVS",
-            "**/config.gypi",
-            "**/npm-debug.log",
-        ];
-
-        for soft_exclude_glob in soft_exclude_globs {
-            let mut should_keep_glob = true;
-
-            for include_pattern in include_patterns {
-                if include_pattern.matches_path(&PathBuf::from(soft_exclude_glob)) {
-                    should_keep_glob = false;
-                    break;
-                }
-            }
-
-            if should_keep_glob {
-                exclude_patterns.insert(
-                    self.to_pkg_file_pattern(soft_exclude_glob)
-                        .with_context(|| "Failed to create pkg file soft exclude glob pattern.")?,
-                );
-            }
-        }
-
-        // These files are always included, regardless of settings.
-        let exclude_negation_globs = HashSet::from([
-            "package.json",
-            // README, LICENSE, and LICENCE can have any casing or extension.
-            "[rR][eE][aA][dD][mM][eE]",
-            "[rR][eE][aA][dD][mM][eE].*",
-            "[lL][iI][cC][eE][nN][sS][eE]",
-            "[lL][iI][cC][eE][nN][sS][eE].*",
-            "[lL][iI][cC][eE][nN][cC][eE]",
-            "[lL][iI][cC][eE][nN][cC][eE].*",
-        ]);
-
-        for exclude_negation_glob in exclude_negation_globs {
-            exclude_negation_patterns.insert(
-                self.to_pkg_file_pattern(exclude_negation_glob)
-                    .with_context(|| "Failed to create pkg file exclude negation glob pattern.")?,
-            );
-        }
-
-        self.get_npm_ignore_globs_if_any_in(&mut exclude_patterns, &mut exclude_negation_patterns)
-            .with_context(|| "Failed to get pkg file exclusion patterns from ignore file.")?;
-
-        let exclude_patterns = exclude_patterns.into_iter().collect();
-        let exclude_negation_patterns = exclude_negation_patterns.into_iter().collect();
-
-        Ok((exclude_patterns, exclude_negation_patterns))
-    }
-
-    fn get_npm_ignore_globs_if_any_in(
-        &self,
-        exclude_patterns: &mut HashSet<Pattern>,
-        exclude_negation_patterns: &mut HashSet<Pattern>,
-    ) -> Result<()> {
-        let file_contents = fs::read_to_string(self.pkg_dir.join(".npmignore"));
-
-        if file_contents.is_err() {
-            return Ok(());
-        }
-
-        for line in file_contents.unwrap().split('\n') {
-            let line = line.trim_start();
-
-            if line.is_empty() {
-                continue;
-            }
-
-            if line.starts_with('#') {
-                continue;
-            }
-
-            if let Some(line) = line.strip_prefix('!') {
-                if !line.is_empty() {
-                    exclude_negation_patterns
-                        .insert(self.to_pkg_file_pattern(line.trim_end()).with_context(|| {
-                        "Failed to create pkg file exclude negation glob pattern from ignore file."
-                    })?);
-                }
-            } else {
-                exclude_patterns.insert(self.to_pkg_file_pattern(line.trim_end()).with_context(
-                    || "Failed to create pkg file exclude glob pattern from ignore file.",
-                )?);
-            }
-        }
-
-        Ok(())
-    }
-
-    fn to_pkg_file_pattern(&self, glob: &str) -> Result<Pattern> {
-        let glob_path = self.pkg_dir.join(glob);
-        let glob_path = glob_path.to_str().unwrap();
-
-        let glob_pattern = Pattern::new(glob_path)
-            .with_context(|| format!("Invalid pkg file glob pattern: {glob_path}"))?;
-
-        Ok(glob_pattern)
-    }
 }
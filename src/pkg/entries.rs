@@ -1,41 +1,83 @@
 use crate::fs::file::FileExt;
-use crate::fs::path::path_matches_a_pattern_in;
 use crate::pkg::contents::PkgContents;
+use crate::pkg::types::{resolve_types_entries, PkgTypesEntry};
 use anyhow::{bail, Context, Result};
 use json::JsonValue;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 use strum_macros::Display;
 
 pub struct PkgEntry {
     pub name: String,
     pub path: PathBuf,
     pub ext: FileExt,
-    contents: Rc<PkgContents>,
+    /// The public subpath (e.g. "." or "./utils") that consumers import this entry
+    /// through, if any.
+    pub subpath: Option<String>,
+    pub contents: Arc<PkgContents>,
 }
 
 pub struct PkgEntries {
     pub main: HashMap<String, PkgEntry>,
+    pub module: HashMap<String, PkgEntry>,
+    pub jsnext_main: HashMap<String, PkgEntry>,
+    pub unpkg: HashMap<String, PkgEntry>,
+    pub jsdelivr: HashMap<String, PkgEntry>,
     pub bin: HashMap<String, PkgEntry>,
     pub browser: HashMap<String, PkgEntry>,
     pub exports: HashMap<String, PkgEntry>,
+    /// Declaration files TypeScript resolves for each public subpath.
+    pub types: BTreeMap<String, PkgTypesEntry>,
 }
 
-#[derive(Display, Debug, Clone)]
+// Variants are ordered the way entries are reported.
+#[derive(Display, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PkgEntryType {
     #[strum(serialize = "main")]
     Main,
+    #[strum(serialize = "module")]
+    Module,
+    #[strum(serialize = "jsnext:main")]
+    JsNextMain,
+    #[strum(serialize = "unpkg")]
+    Unpkg,
+    #[strum(serialize = "jsdelivr")]
+    Jsdelivr,
     #[strum(serialize = "bin")]
     Bin,
     #[strum(serialize = "browser")]
     Browser,
     #[strum(serialize = "exports")]
     Exports,
+    /// Declaration files TypeScript loads for a subpath, which are only compared when
+    /// both versions have some.
+    #[strum(serialize = "types")]
+    Types,
+}
+
+impl PkgEntryType {
+    /// Whether the entry type is a single package.json field pointing to one file,
+    /// as opposed to a field that maps several names to files.
+    pub fn is_single_file_field(&self) -> bool {
+        matches!(
+            self,
+            PkgEntryType::Main
+                | PkgEntryType::Module
+                | PkgEntryType::JsNextMain
+                | PkgEntryType::Unpkg
+                | PkgEntryType::Jsdelivr
+        )
+    }
 }
 
 impl PkgEntry {
-    pub fn new(name: String, path: PathBuf, contents: Rc<PkgContents>) -> Result<Self> {
+    pub fn new(
+        name: String,
+        path: PathBuf,
+        subpath: Option<String>,
+        contents: Arc<PkgContents>,
+    ) -> Result<Self> {
         let pkg_file_path = contents.pkg_dir.join(&path);
         let pkg_entry_ext = FileExt::from(&path);
 
@@ -44,9 +86,10 @@ impl PkgEntry {
         }
 
         let entry = Self {
-            contents: Rc::clone(&contents),
+            contents: Arc::clone(&contents),
             path: path.to_owned(),
             ext: pkg_entry_ext,
+            subpath,
             name,
         };
 
@@ -56,7 +99,7 @@ impl PkgEntry {
 
         if !pkg_file_path.try_exists()? {
             bail!("Entry '{}' does not exist.", path.display());
-        } else if !path_matches_a_pattern_in(&pkg_file_path, &contents.include_patterns) {
+        } else if !contents.has_file(&path)? {
             bail!("Entry '{}' exists but is not in 'files'.", path.display());
         }
 
@@ -68,41 +111,59 @@ impl PkgEntry {
             .load_file(&self.path)
             .with_context(|| format!("Failed to load package entry file: {}", self.path.display()))
     }
-
-    pub fn dir_path(&self) -> PathBuf {
-        if let Some(dir_path_entry) = self.path.parent() {
-            self.contents.pkg_dir.join(dir_path_entry)
-        } else {
-            self.contents.pkg_dir.to_owned()
-        }
-    }
 }
 
 impl PkgEntries {
-    pub fn new(pkg_json: &JsonValue, pkg_contents: Rc<PkgContents>) -> Result<PkgEntries> {
-        let main = Self::resolve_main_entry(pkg_json, Rc::clone(&pkg_contents))
+    pub fn new(pkg_json: &JsonValue, pkg_contents: Arc<PkgContents>) -> Result<PkgEntries> {
+        let main = Self::resolve_main_entry(pkg_json, Arc::clone(&pkg_contents))
             .with_context(|| "Failed to resolve main entry.")?;
 
-        let browser = Self::resolve_browser_entries(pkg_json, Rc::clone(&pkg_contents))
+        let module = Self::resolve_optional_string_entry("module".into(), pkg_json, &pkg_contents)
+            .with_context(|| "Failed to resolve module entry.")?;
+
+        let jsnext_main =
+            Self::resolve_optional_string_entry("jsnext:main".into(), pkg_json, &pkg_contents)
+                .with_context(|| "Failed to resolve jsnext:main entry.")?;
+
+        let unpkg = Self::resolve_optional_string_entry("unpkg".into(), pkg_json, &pkg_contents)
+            .with_context(|| "Failed to resolve unpkg entry.")?;
+
+        let jsdelivr =
+            Self::resolve_optional_string_entry("jsdelivr".into(), pkg_json, &pkg_contents)
+                .with_context(|| "Failed to resolve jsdelivr entry.")?;
+
+        let browser = Self::resolve_browser_entries(pkg_json, Arc::clone(&pkg_contents))
             .with_context(|| "Failed to resolve browser entries.")?;
 
-        let bin = Self::resolve_bin_entries(pkg_json, Rc::clone(&pkg_contents))
+        let bin = Self::resolve_bin_entries(pkg_json, Arc::clone(&pkg_contents))
             .with_context(|| "Failed to resolve bin entries.")?;
 
-        let exports = Self::resolve_exports_entries(pkg_json, Rc::clone(&pkg_contents))
+        let exports = Self::resolve_exports_entries(pkg_json, Arc::clone(&pkg_contents))
             .with_context(|| "Failed to resolve exports entries.")?;
 
+        let pkg_files = pkg_contents
+            .file_list()
+            .with_context(|| "Failed to list package files for types resolution.")?;
+
+        let types = resolve_types_entries(pkg_json, &pkg_files)
+            .with_context(|| "Failed to resolve types entries.")?;
+
         Ok(Self {
             main,
+            module,
+            jsnext_main,
+            unpkg,
+            jsdelivr,
             bin,
             browser,
             exports,
+            types,
         })
     }
 
     fn resolve_main_entry(
         pkg_json: &JsonValue,
-        pkg_contents: Rc<PkgContents>,
+        pkg_contents: Arc<PkgContents>,
     ) -> Result<HashMap<String, PkgEntry>> {
         let name = String::from("main");
 
@@ -110,51 +171,204 @@ impl PkgEntries {
         let entry_path = if entry_path.is_string() {
             entry_path.to_string()
         } else {
+            // Node only falls back to index.js when it exists, which packages that only
+            // declare an exports field commonly omit.
+            if !pkg_contents.has_file(&PathBuf::from("index.js"))? {
+                return Ok(HashMap::new());
+            }
+
             "index.js".to_string()
         };
 
-        let entry = PkgEntry::new(name.to_owned(), entry_path.into(), Rc::clone(&pkg_contents))?;
+        let entry = PkgEntry::new(
+            name.to_owned(),
+            entry_path.into(),
+            Some(".".into()),
+            Arc::clone(&pkg_contents),
+        )?;
+
         let entry = (name, entry);
 
         Ok(HashMap::from([entry]))
     }
 
+    fn resolve_optional_string_entry(
+        field_name: String,
+        pkg_json: &JsonValue,
+        pkg_contents: &Arc<PkgContents>,
+    ) -> Result<HashMap<String, PkgEntry>> {
+        // CDN fields are served by URL rather than imported, so they have no subpath.
+        let subpath = match field_name.as_str() {
+            "unpkg" | "jsdelivr" => None,
+            _ => Some(".".into()),
+        };
+
+        let entry_path = &pkg_json[&field_name];
+
+        if !entry_path.is_string() {
+            return Ok(HashMap::new());
+        }
+
+        let entry_path = PathBuf::from(entry_path.to_string());
+
+        // CDN fields (unpkg, jsdelivr) may point to non-script assets such as stylesheets,
+        // which are covered by the asset comparison instead.
+        if FileExt::from(&entry_path).is_other() {
+            return Ok(HashMap::new());
+        }
+
+        let entry = PkgEntry::new(
+            field_name.to_owned(),
+            entry_path,
+            subpath,
+            Arc::clone(pkg_contents),
+        )?;
+
+        let entry = (field_name, entry);
+
+        Ok(HashMap::from([entry]))
+    }
+
     fn resolve_browser_entries(
         pkg_json: &JsonValue,
-        pkg_contents: Rc<PkgContents>,
+        pkg_contents: Arc<PkgContents>,
     ) -> Result<HashMap<String, PkgEntry>> {
         Self::resolve_string_or_object_entries("browser".into(), pkg_json, pkg_contents)
     }
 
     fn resolve_bin_entries(
         pkg_json: &JsonValue,
-        pkg_contents: Rc<PkgContents>,
+        pkg_contents: Arc<PkgContents>,
     ) -> Result<HashMap<String, PkgEntry>> {
         Self::resolve_string_or_object_entries("bin".into(), pkg_json, pkg_contents)
     }
 
+    // See https://nodejs.org/api/packages.html#package-entry-points
     fn resolve_exports_entries(
         pkg_json: &JsonValue,
-        pkg_contents: Rc<PkgContents>,
+        pkg_contents: Arc<PkgContents>,
     ) -> Result<HashMap<String, PkgEntry>> {
-        Self::resolve_string_or_object_entries("exports".into(), pkg_json, pkg_contents)
+        let property = &pkg_json["exports"];
+        let mut entries: HashMap<String, PkgEntry> = HashMap::new();
+
+        // The exports field is either a map of subpaths, or a target (string, conditions
+        // object or fallback array) for the "." subpath.
+        let is_subpath_map =
+            property.is_object() && property.entries().any(|(key, _)| key.starts_with('.'));
+
+        if is_subpath_map {
+            for (subpath, target) in property.entries() {
+                Self::resolve_exports_target(
+                    subpath,
+                    target,
+                    &mut Vec::new(),
+                    &mut entries,
+                    &pkg_contents,
+                )
+                .with_context(|| format!("Failed to resolve exports subpath '{subpath}'."))?;
+            }
+        } else {
+            Self::resolve_exports_target(
+                ".",
+                property,
+                &mut Vec::new(),
+                &mut entries,
+                &pkg_contents,
+            )?;
+        }
+
+        Ok(entries)
+    }
+
+    fn resolve_exports_target(
+        subpath: &str,
+        target: &JsonValue,
+        conditions: &mut Vec<String>,
+        entries: &mut HashMap<String, PkgEntry>,
+        pkg_contents: &Arc<PkgContents>,
+    ) -> Result<()> {
+        // Subpath patterns cannot be resolved to a single module to analyze.
+        if subpath.contains('*') {
+            return Ok(());
+        }
+
+        if target.is_string() {
+            let entry_path = PathBuf::from(target.to_string());
+
+            // Declaration files are not runtime entries, and non-script targets such as
+            // package.json or stylesheets are covered by the asset comparison.
+            if conditions.iter().any(|condition| condition.eq("types"))
+                || FileExt::from(&entry_path).is_other()
+            {
+                return Ok(());
+            }
+
+            let entry_name = if conditions.is_empty() {
+                subpath.to_string()
+            } else {
+                format!("{subpath} ({})", conditions.join(", "))
+            };
+
+            let entry = PkgEntry::new(
+                entry_name.to_owned(),
+                entry_path,
+                Some(subpath.into()),
+                Arc::clone(pkg_contents),
+            )?;
+
+            entries.insert(entry_name, entry);
+        } else if target.is_object() {
+            for (condition, nested_target) in target.entries() {
+                conditions.push(condition.into());
+                Self::resolve_exports_target(
+                    subpath,
+                    nested_target,
+                    conditions,
+                    entries,
+                    pkg_contents,
+                )?;
+                conditions.pop();
+            }
+        } else if target.is_array() {
+            // Fallback arrays are resolved to their first valid target.
+            if let Some(first_target) = target.members().find(|member| !member.is_null()) {
+                Self::resolve_exports_target(
+                    subpath,
+                    first_target,
+                    conditions,
+                    entries,
+                    pkg_contents,
+                )?;
+            }
+        }
+
+        // A null target explicitly hides the subpath, so there is nothing to resolve.
+        Ok(())
     }
 
     fn resolve_string_or_object_entries(
         field_name: String,
         pkg_json: &JsonValue,
-        pkg_contents: Rc<PkgContents>,
+        pkg_contents: Arc<PkgContents>,
     ) -> Result<HashMap<String, PkgEntry>> {
         let property = &pkg_json[&field_name];
         let mut entries: HashMap<String, PkgEntry> = HashMap::new();
 
         if property.is_string() {
+            // A string browser field replaces the main entry, so it shares its subpath.
+            let subpath = if field_name.eq("browser") {
+                Some(".".into())
+            } else {
+                None
+            };
+
             entries.insert(
                 field_name.to_owned(),
                 PkgEntry::new(
                     field_name,
                     property.to_string().into(),
-                    Rc::clone(&pkg_contents),
+                    subpath,
+                    Arc::clone(&pkg_contents),
                 )?,
             );
 
@@ -198,34 +412,18 @@ impl PkgEntries {
                 }
             }
 
-            if entry_value.is_string() {
-                let entry = PkgEntry::new(
-                    entry_name.into(),
-                    entry_value.to_string().into(),
-                    Rc::clone(&pkg_contents),
-                )?;
-
-                entries.insert(entry_name.into(), entry);
-                continue;
-            }
-
-            if !entry_value.is_object() {
-                bail!("Expected '{field_name}' field to be an object.");
+            if !entry_value.is_string() {
+                bail!("Expected '{entry_name}' in '{field_name}' field to be a string.");
             }
 
-            for (sub_entry_name, sub_entry_path) in entry_value.entries() {
-                if !sub_entry_path.is_string() {
-                    bail!("Expected '{sub_entry_name}' in '{field_name}' field to be a string.");
-                }
+            let entry = PkgEntry::new(
+                entry_name.into(),
+                entry_value.to_string().into(),
+                None,
+                Arc::clone(&pkg_contents),
+            )?;
 
-                let entry = PkgEntry::new(
-                    sub_entry_name.into(),
-                    sub_entry_path.to_string().into(),
-                    Rc::clone(&pkg_contents),
-                )?;
-
-                entries.insert(sub_entry_name.into(), entry);
-            }
+            entries.insert(entry_name.into(), entry);
         }
 
         Ok(entries)
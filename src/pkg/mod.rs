@@ -2,23 +2,31 @@ use crate::pkg::contents::PkgContents;
 use crate::pkg::entries::PkgEntries;
 use anyhow::{bail, Context, Result};
 use json::JsonValue;
+use std::collections::BTreeMap;
 use std::fs::read_to_string;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::sync::Arc;
 use url::Url;
 
 pub mod contents;
 pub mod entries;
+pub mod packlist;
 pub mod registry;
 pub mod tarball;
+pub mod types;
+
+/// Fields listing the packages a package imports at runtime, in order of precedence.
+const DEPENDENCY_FIELDS: [&str; 3] = ["dependencies", "peerDependencies", "optionalDependencies"];
 
 pub struct Pkg {
     pub name: String,
     pub version: String,
+    /// Version ranges of runtime dependencies, by package name.
+    pub dependencies: BTreeMap<String, String>,
     pub dir: PathBuf,
     pub registry_url: Url,
     pub entries: PkgEntries,
-    pub contents: Rc<PkgContents>,
+    pub contents: Arc<PkgContents>,
 }
 
 impl Pkg {
@@ -26,16 +34,29 @@ impl Pkg {
         dir: PathBuf,
         config: JsonValue,
         registry_url: Url,
-        contents: Rc<PkgContents>,
+        contents: Arc<PkgContents>,
         entries: PkgEntries,
     ) -> Self {
         let name = config["name"].to_string();
         let version = config["version"].to_string();
-        let contents = Rc::clone(&contents);
+        let contents = Arc::clone(&contents);
+
+        let mut dependencies = BTreeMap::new();
+
+        for field_name in DEPENDENCY_FIELDS {
+            for (dependency_name, range) in config[field_name].entries() {
+                if let Some(range) = range.as_str() {
+                    dependencies
+                        .entry(dependency_name.into())
+                        .or_insert(range.into());
+                }
+            }
+        }
 
         Self {
             name,
             version,
+            dependencies,
             dir,
             registry_url,
             contents,
@@ -84,7 +105,7 @@ impl Pkg {
 
             for line in npmrc.split('\n') {
                 if line.trim_start().starts_with("registry=") {
-                    return Ok(Url::parse(line.split('=').last().unwrap())?);
+                    return Ok(Url::parse(line.split('=').next_back().unwrap())?);
                 }
             }
         }
@@ -0,0 +1,593 @@
+use crate::fs::ignore::IgnoreRules;
+use anyhow::{Context, Result};
+use json::JsonValue;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use strum_macros::Display;
+
+// See https://github.com/npm/npm-packlist for the rules reproduced here.
+
+/// Files that are ignored by default, but can be included through `files` or negated
+/// ignore rules.
+const DEFAULT_IGNORE_RULES: [&str; 22] = [
+    ".npmignore",
+    ".gitignore",
+    "**/.git",
+    "**/.svn",
+    "**/.hg",
+    "**/CVS",
+    "**/.git/**",
+    "**/.svn/**",
+    "**/.hg/**",
+    "**/CVS/**",
+    "/.lock-wscript",
+    "/.wafpickle-*",
+    "/build/config.gypi",
+    "npm-debug.log",
+    "**/.npmrc",
+    ".*.swp",
+    ".DS_Store",
+    "**/.DS_Store/**",
+    "._*",
+    "**/._*/**",
+    "*.orig",
+    "/archived-packages/**",
+];
+
+/// Files at the package root that are never packed, regardless of settings.
+const STRICT_ROOT_EXCLUDES: [&str; 5] = [
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "npm-shrinkwrap.json",
+];
+
+/// Directories that are never packed, at any depth.
+const STRICT_DIR_EXCLUDES: [&str; 2] = [".git", "node_modules"];
+
+/// Files that are never packed, at any depth.
+const STRICT_FILE_EXCLUDES: [&str; 1] = [".npmrc"];
+
+/// Files that are never packed below the package root.
+const STRICT_NESTED_FILE_EXCLUDES: [&str; 1] = [".npmignore"];
+
+/// File name stems that are always packed when found at the package root.
+const ALWAYS_INCLUDED_ROOT_STEMS: [&str; 4] = ["readme", "copying", "license", "licence"];
+
+/// Why a path is or isn't part of the pack list.
+#[derive(Clone, Display)]
+pub enum PackListReason {
+    #[strum(serialize = "not matched by any rule")]
+    Unmatched,
+    #[strum(to_string = "matched '{rule}' in {origin}")]
+    MatchedRule { rule: String, origin: RuleOrigin },
+    #[strum(serialize = "always packed by npm")]
+    AlwaysIncluded,
+    #[strum(serialize = "never packed by npm")]
+    AlwaysExcluded,
+}
+
+#[derive(Clone, Display)]
+pub enum RuleOrigin {
+    #[strum(serialize = "npm defaults")]
+    Defaults,
+    #[strum(serialize = "package.json 'files'")]
+    FilesField,
+    #[strum(to_string = "{path}")]
+    IgnoreFile { path: String },
+}
+
+/// A path of the package directory, relative to it. Excluded directories are listed
+/// as a whole, as npm never walks them.
+pub struct PackListPath {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub is_included: bool,
+    pub reason: PackListReason,
+    pub size: u64,
+}
+
+struct RuleSet {
+    base_dir: PathBuf,
+    rule_groups: Vec<(RuleOrigin, IgnoreRules)>,
+}
+
+struct PackListWalker<'pkg> {
+    pkg_dir: &'pkg Path,
+    files_field_globs: Option<Vec<(bool, String)>>,
+    rule_sets: Vec<RuleSet>,
+    required_files: HashSet<PathBuf>,
+    paths: Vec<PackListPath>,
+}
+
+/// Lists the paths of the package directory, along with whether `npm pack` would include
+/// them and why.
+pub fn get_pack_list_paths(pkg_dir: &Path, pkg_json: &JsonValue) -> Result<Vec<PackListPath>> {
+    let files_field = &pkg_json["files"];
+    let files_field_globs = if files_field.is_array() {
+        Some(get_files_field_globs(files_field))
+    } else {
+        None
+    };
+
+    let mut walker = PackListWalker {
+        pkg_dir,
+        files_field_globs,
+        rule_sets: Vec::new(),
+        required_files: get_required_files(pkg_json),
+        paths: Vec::new(),
+    };
+
+    walker
+        .walk_dir(Path::new(""), (true, PackListReason::Unmatched))
+        .with_context(|| format!("Failed to walk package directory: {}", pkg_dir.display()))?;
+
+    Ok(walker.paths)
+}
+
+impl<'pkg> PackListWalker<'pkg> {
+    fn walk_dir(&mut self, dir: &Path, dir_decision: (bool, PackListReason)) -> Result<()> {
+        let rule_groups = self
+            .get_rules_of_dir(dir)
+            .with_context(|| format!("Failed to read ignore rules of: {}", dir.display()))?;
+
+        self.rule_sets.push(RuleSet {
+            base_dir: dir.to_owned(),
+            rule_groups,
+        });
+
+        self.walk_dir_entries(dir, dir_decision)?;
+        self.rule_sets.pop();
+
+        Ok(())
+    }
+
+    fn walk_dir_entries(&mut self, dir: &Path, dir_decision: (bool, PackListReason)) -> Result<()> {
+        let mut dir_entries: Vec<(PathBuf, bool)> = Vec::new();
+
+        for entry in fs::read_dir(self.pkg_dir.join(dir))? {
+            let entry = entry?;
+            let entry_path = dir.join(entry.file_name());
+            let is_dir = entry.path().is_dir();
+
+            dir_entries.push((entry_path, is_dir));
+        }
+
+        dir_entries.sort();
+
+        for (entry_path, is_dir) in dir_entries {
+            if is_strictly_excluded(&entry_path, is_dir) {
+                self.add_path(entry_path, is_dir, (false, PackListReason::AlwaysExcluded))?;
+                continue;
+            }
+
+            let mut decision = self.get_decision_of(&entry_path, is_dir, &dir_decision);
+
+            if !is_dir {
+                if !decision.0 && self.is_required(&entry_path) {
+                    self.add_path(entry_path, is_dir, (true, PackListReason::AlwaysIncluded))?;
+                } else {
+                    self.add_path(entry_path, is_dir, decision)?;
+                }
+
+                continue;
+            }
+
+            // Excluded directories are still walked when a negated rule may re-include
+            // something inside them. npm re-includes a directory containing a required
+            // file, so that its files are packed unless a rule excludes them directly.
+            if !decision.0 {
+                if self.has_required_file_within(&entry_path) {
+                    decision = (true, PackListReason::AlwaysIncluded);
+                } else if !self.may_include_within(&entry_path) {
+                    self.add_path(entry_path, is_dir, decision)?;
+                    continue;
+                }
+            }
+
+            self.walk_dir(&entry_path, decision)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_path(
+        &mut self,
+        path: PathBuf,
+        is_dir: bool,
+        decision: (bool, PackListReason),
+    ) -> Result<()> {
+        let (is_included, reason) = decision;
+
+        let size = if is_dir {
+            0
+        } else {
+            fs::metadata(self.pkg_dir.join(&path))
+                .with_context(|| format!("Failed to read file metadata: {}", path.display()))?
+                .len()
+        };
+
+        self.paths.push(PackListPath {
+            path,
+            is_dir,
+            is_included,
+            reason,
+            size,
+        });
+
+        Ok(())
+    }
+
+    /// The default rules apply in every directory, followed by the entries of the files
+    /// field that lie within it, and the directory's own ignore file.
+    fn get_rules_of_dir(&self, dir: &Path) -> Result<Vec<(RuleOrigin, IgnoreRules)>> {
+        let mut default_rules = IgnoreRules::default();
+
+        for rule in DEFAULT_IGNORE_RULES {
+            default_rules.push(rule)?;
+        }
+
+        let mut rule_groups = vec![(RuleOrigin::Defaults, default_rules)];
+        let is_root_dir = dir.as_os_str().is_empty();
+
+        if let Some(files_field_globs) = &self.files_field_globs {
+            let files_field_rules = get_files_field_rules(files_field_globs, dir)
+                .with_context(|| "Failed to parse 'files' field entries.")?;
+
+            rule_groups.push((RuleOrigin::FilesField, files_field_rules));
+
+            // When a files field is given, the root ignore file does not apply; ignore
+            // files in subdirectories still do.
+            if is_root_dir {
+                return Ok(rule_groups);
+            }
+        }
+
+        if let Some((ignore_file_path, rules)) = read_ignore_file_in(self.pkg_dir, dir)? {
+            let path = ignore_file_path.display().to_string();
+
+            rule_groups.push((RuleOrigin::IgnoreFile { path }, rules));
+        }
+
+        Ok(rule_groups)
+    }
+
+    fn get_decision_of(
+        &self,
+        path: &Path,
+        is_dir: bool,
+        parent_decision: &(bool, PackListReason),
+    ) -> (bool, PackListReason) {
+        let mut decision = parent_decision.to_owned();
+
+        // Rule sets are ordered from the package root to the deepest directory, and the
+        // last matching rule of the last matching set wins.
+        for rule_set in self.rule_sets.iter() {
+            let relative_path = match path.strip_prefix(&rule_set.base_dir) {
+                Ok(relative_path) => relative_path,
+                Err(_) => continue,
+            };
+
+            for (origin, rules) in rule_set.rule_groups.iter() {
+                if let Some(rule) = rules.get_matching_rule(relative_path, is_dir) {
+                    let reason = PackListReason::MatchedRule {
+                        rule: rule.source.to_owned(),
+                        origin: origin.to_owned(),
+                    };
+
+                    decision = (rule.is_negated, reason);
+                }
+            }
+        }
+
+        decision
+    }
+
+    fn may_include_within(&self, dir: &Path) -> bool {
+        self.rule_sets.iter().any(|rule_set| {
+            dir.strip_prefix(&rule_set.base_dir)
+                .is_ok_and(|relative_dir| {
+                    rule_set
+                        .rule_groups
+                        .iter()
+                        .any(|(_, rules)| rules.may_include_within(relative_dir))
+                })
+        })
+    }
+
+    fn has_required_file_within(&self, dir: &Path) -> bool {
+        self.required_files
+            .iter()
+            .any(|required_file| required_file.starts_with(dir))
+    }
+
+    fn is_required(&self, path: &Path) -> bool {
+        if self.required_files.contains(path) {
+            return true;
+        }
+
+        let is_root_file = path.components().count().eq(&1);
+
+        if !is_root_file {
+            return false;
+        }
+
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(file_name) => file_name.to_lowercase(),
+            None => return false,
+        };
+
+        // Backup files (e.g. "README~") are not considered.
+        if file_name.ends_with('~') || file_name.ends_with('$') {
+            return false;
+        }
+
+        let file_stem = file_name.split('.').next().unwrap_or_default();
+
+        ALWAYS_INCLUDED_ROOT_STEMS.contains(&file_stem)
+    }
+}
+
+fn get_required_files(pkg_json: &JsonValue) -> HashSet<PathBuf> {
+    let mut required_files = HashSet::from([PathBuf::from("package.json")]);
+
+    let mut add_required_file = |path: &JsonValue| {
+        if path.is_string() {
+            let path = path.to_string();
+            let path = path.trim_start_matches("./").trim_start_matches('/');
+
+            required_files.insert(PathBuf::from(path));
+        }
+    };
+
+    add_required_file(&pkg_json["main"]);
+    add_required_file(&pkg_json["browser"]);
+
+    let bin_field = &pkg_json["bin"];
+
+    if bin_field.is_object() {
+        for (_, bin_path) in bin_field.entries() {
+            add_required_file(bin_path);
+        }
+    } else {
+        add_required_file(bin_field);
+    }
+
+    required_files
+}
+
+fn get_files_field_globs(files_field: &JsonValue) -> Vec<(bool, String)> {
+    let mut globs = Vec::new();
+
+    for file_glob in files_field.members() {
+        let file_glob = file_glob.to_string();
+        let (is_negated, file_glob) = match file_glob.strip_prefix('!') {
+            Some(file_glob) => (true, file_glob),
+            None => (false, file_glob.as_str()),
+        };
+
+        let file_glob = file_glob
+            .trim_start_matches("./")
+            .trim_start_matches('/')
+            .trim_end_matches('/');
+
+        // Paths outside the package root are invalid for npm.
+        if file_glob.is_empty() || file_glob.starts_with("../") {
+            continue;
+        }
+
+        globs.push((is_negated, file_glob.to_string()));
+    }
+
+    globs
+}
+
+fn get_files_field_rules(files_field_globs: &[(bool, String)], dir: &Path) -> Result<IgnoreRules> {
+    let mut rules = IgnoreRules::default();
+    let is_root_dir = dir.as_os_str().is_empty();
+
+    // Everything is excluded, except for what the files field includes.
+    if is_root_dir {
+        rules.push("*")?;
+    }
+
+    for (is_negated, file_glob) in files_field_globs {
+        let rule_prefix = if *is_negated { "" } else { "!" };
+
+        if is_root_dir {
+            // An entry naming a directory includes everything within it.
+            rules.push(&format!("{rule_prefix}/{file_glob}"))?;
+            rules.push(&format!("{rule_prefix}/{file_glob}/**"))?;
+
+            continue;
+        }
+
+        // Entries within a subdirectory are matched again after its default rules, so
+        // that explicitly listed files are packed even when a default rule excludes them.
+        if let Ok(relative_glob) = Path::new(file_glob).strip_prefix(dir) {
+            if !relative_glob.as_os_str().is_empty() {
+                rules.push(&format!("{rule_prefix}/{}", relative_glob.display()))?;
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+fn read_ignore_file_in(pkg_dir: &Path, dir: &Path) -> Result<Option<(PathBuf, IgnoreRules)>> {
+    // A .gitignore is only used when there is no .npmignore next to it.
+    for ignore_file_name in [".npmignore", ".gitignore"] {
+        let ignore_file_path = dir.join(ignore_file_name);
+        let ignore_file_full_path = pkg_dir.join(&ignore_file_path);
+
+        if ignore_file_full_path.is_file() {
+            let ignore_file = fs::read_to_string(&ignore_file_full_path)?;
+            let rules = IgnoreRules::parse(&ignore_file)
+                .with_context(|| format!("Invalid ignore file: {}", ignore_file_path.display()))?;
+
+            return Ok(Some((ignore_file_path, rules)));
+        }
+    }
+
+    Ok(None)
+}
+
+fn is_strictly_excluded(path: &Path, is_dir: bool) -> bool {
+    let file_name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(file_name) => file_name,
+        None => return false,
+    };
+
+    if is_dir {
+        return STRICT_DIR_EXCLUDES.contains(&file_name);
+    }
+
+    if STRICT_FILE_EXCLUDES.contains(&file_name) {
+        return true;
+    }
+
+    let is_root_file = path.components().count().eq(&1);
+
+    if is_root_file {
+        STRICT_ROOT_EXCLUDES.contains(&file_name)
+    } else {
+        STRICT_NESTED_FILE_EXCLUDES.contains(&file_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lists the files packed from a package directory holding the given files, which
+    /// all contain a single line, and the given .npmignore file if not empty.
+    fn get_packed_files(
+        fixture_name: &str,
+        pkg_json: &str,
+        npmignore: &str,
+        file_paths: &[&str],
+    ) -> Vec<String> {
+        let pkg_dir = std::env::temp_dir().join(format!(
+            "breakpoint-packlist-{fixture_name}-{}",
+            std::process::id()
+        ));
+
+        let _ = fs::remove_dir_all(&pkg_dir);
+
+        for file_path in file_paths {
+            let full_path = pkg_dir.join(file_path);
+
+            fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+            fs::write(full_path, "x\n").unwrap();
+        }
+
+        fs::write(pkg_dir.join("package.json"), pkg_json).unwrap();
+
+        if !npmignore.is_empty() {
+            fs::write(pkg_dir.join(".npmignore"), npmignore).unwrap();
+        }
+
+        let pack_list_paths = get_pack_list_paths(&pkg_dir, &json::parse(pkg_json).unwrap());
+
+        fs::remove_dir_all(&pkg_dir).unwrap();
+
+        let mut packed_files: Vec<String> = pack_list_paths
+            .unwrap()
+            .into_iter()
+            .filter(|pack_list_path| pack_list_path.is_included)
+            .map(|pack_list_path| pack_list_path.path.display().to_string())
+            .collect();
+
+        packed_files.sort();
+        packed_files
+    }
+
+    // The expected lists are the output of `npm pack --dry-run` with npm 10.8.2.
+
+    #[test]
+    fn files_field_only_adds_root_readme_and_license_files() {
+        let packed_files = get_packed_files(
+            "files-field",
+            r#"{"main": "lib/index.js", "browser": "dist/browser.js", "files": ["docs"]}"#,
+            "",
+            &[
+                "README.md",
+                "LICENSE",
+                "CHANGELOG.md",
+                "HISTORY.md",
+                "NOTICE",
+                "Copying.txt",
+                "licence",
+                "readme.md~",
+                "lib/index.js",
+                "lib/README",
+                "lib/LICENSE",
+                "lib/other.js",
+                "dist/browser.js",
+                "dist/README.md",
+                "docs/a.md",
+                "docs/CHANGELOG.md",
+                "docs/readme.txt",
+            ],
+        );
+
+        assert_eq!(
+            packed_files,
+            [
+                "Copying.txt",
+                "LICENSE",
+                "README.md",
+                "dist/browser.js",
+                "docs/CHANGELOG.md",
+                "docs/a.md",
+                "docs/readme.txt",
+                "lib/index.js",
+                "licence",
+                "package.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn ignored_directories_with_required_files_are_re_included() {
+        let packed_files = get_packed_files(
+            "ignore-file",
+            r#"{"main": "lib/index.js", "browser": "dist/browser.js", "bin": {"pk": "bin.js"}}"#,
+            "lib\ndist\n*.js\nREADME.md\n",
+            &[
+                "README.md",
+                "NOTICE",
+                "bin.js",
+                "lib/index.js",
+                "lib/README",
+                "lib/LICENSE",
+                "lib/other.js",
+                "dist/browser.js",
+                "dist/LICENSE",
+                "dist/other.txt",
+                "sub/README.md",
+                "sub/CHANGES.md",
+                "sub/a.js",
+            ],
+        );
+
+        assert_eq!(
+            packed_files,
+            [
+                "NOTICE",
+                "README.md",
+                "bin.js",
+                "dist/LICENSE",
+                "dist/browser.js",
+                "dist/other.txt",
+                "lib/LICENSE",
+                "lib/README",
+                "lib/index.js",
+                "package.json",
+                "sub/CHANGES.md",
+            ]
+        );
+    }
+}
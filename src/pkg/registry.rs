@@ -1,12 +1,12 @@
 use crate::pkg::contents::PkgContents;
 use crate::pkg::entries::PkgEntries;
-use crate::pkg::tarball::PkgTarball;
+use crate::pkg::tarball::{PkgTarball, TarballLimits};
 use crate::pkg::Pkg;
 use anyhow::{bail, Context, Result};
 use base64::{engine::general_purpose::STANDARD as BASE_64_STANDARD, Engine as _};
 use json::JsonValue;
-use std::path::PathBuf;
-use std::rc::Rc;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use url::Url;
 
 pub fn load_from_dir(pkg_dir: PathBuf) -> Result<Pkg> {
@@ -21,9 +21,9 @@ pub fn load_from_dir(pkg_dir: PathBuf) -> Result<Pkg> {
     let pkg_contents = PkgContents::new(pkg_dir.to_owned(), &pkg_json, None)
         .with_context(|| "Failed to create package contents from file system.")?;
 
-    let pkg_contents = Rc::new(pkg_contents);
+    let pkg_contents = Arc::new(pkg_contents);
 
-    let pkg_entries = PkgEntries::new(&pkg_json, Rc::clone(&pkg_contents))
+    let pkg_entries = PkgEntries::new(&pkg_json, Arc::clone(&pkg_contents))
         .with_context(|| "Failed to create package entries.")?;
 
     Ok(Pkg::new(
@@ -35,22 +35,47 @@ pub fn load_from_dir(pkg_dir: PathBuf) -> Result<Pkg> {
     ))
 }
 
-pub fn fetch_from_server(local_pkg: &Pkg) -> Result<Pkg> {
-    let pkg_dir = &local_pkg.dir;
+/// Loads only the entries of an installed package, e.g. a dependency in `node_modules`.
+pub fn load_entries_from_dir(pkg_dir: PathBuf) -> Result<PkgEntries> {
+    let pkg_json = Pkg::parse_config_in_dir(&pkg_dir)
+        .with_context(|| "Failed to load and parse package.json config.")?;
+
+    let pkg_contents = PkgContents::new(pkg_dir, &pkg_json, None)
+        .with_context(|| "Failed to create package contents from file system.")?;
+
+    PkgEntries::new(&pkg_json, Arc::new(pkg_contents))
+        .with_context(|| "Failed to create package entries.")
+}
+
+/// Fetches the last published version of the package in a directory. Only its config is
+/// read, so that the local package can be loaded meanwhile.
+pub fn fetch_from_server(pkg_dir: &Path, tarball_limits: &TarballLimits) -> Result<Pkg> {
+    let pkg_json = Pkg::parse_config_in_dir(pkg_dir)
+        .with_context(|| "Failed to load and parse package.json config.")?;
+
+    let pkg_registry_url = Pkg::get_registry_url(pkg_dir)
+        .with_context(|| "Failed to determine package registry URL.")?;
+
+    let pkg_name = pkg_json["name"].to_string();
     let pkg_dir_tmp = pkg_dir.join(".tmp");
-    let pkg_registry_url = local_pkg.registry_url.to_owned();
 
-    let tarball = fetch_last_published_tarball_of(&pkg_dir_tmp, local_pkg)
-        .with_context(|| "Failed to fetch last published tarball from registry.")?;
+    let tarball =
+        fetch_last_published_tarball_of(&pkg_dir_tmp, &pkg_name, &pkg_registry_url, tarball_limits)
+            .with_context(|| "Failed to fetch last published tarball from registry.")?;
 
-    let pkg = download_and_unpack_pkg_tarball(pkg_dir.to_owned(), pkg_registry_url, tarball)
+    let pkg = download_and_unpack_pkg_tarball(pkg_dir.to_path_buf(), pkg_registry_url, tarball)
         .with_context(|| "Failed to download and unpack package tarball from registry.")?;
 
     Ok(pkg)
 }
 
-fn fetch_last_published_tarball_of(pkg_dir: &PathBuf, local_pkg: &Pkg) -> Result<PkgTarball> {
-    let pkg_data_latest = fetch_latest_pkg_info_for(local_pkg)
+fn fetch_last_published_tarball_of(
+    pkg_dir: &PathBuf,
+    pkg_name: &str,
+    pkg_registry_url: &Url,
+    tarball_limits: &TarballLimits,
+) -> Result<PkgTarball> {
+    let pkg_data_latest = fetch_latest_pkg_info_for(pkg_name, pkg_registry_url)
         .with_context(|| "Failed to request package information from registry.")?;
 
     let pkg_version_latest = &pkg_data_latest["dist-tags"]["latest"];
@@ -60,11 +85,12 @@ fn fetch_last_published_tarball_of(pkg_dir: &PathBuf, local_pkg: &Pkg) -> Result
     }
 
     let pkg_version_latest = pkg_version_latest.to_string();
-    let pkg_tarball_name = format!("{}-{}.tar.gz", local_pkg.name, pkg_version_latest);
+    let pkg_tarball_name = format!("{pkg_name}-{pkg_version_latest}.tar.gz");
 
     let pkg_dist = &pkg_data_latest["versions"][&pkg_version_latest]["dist"];
-    let pkg_tarball = get_pkg_tarball_from_dist(pkg_tarball_name, pkg_dir, pkg_dist)
-        .with_context(|| "Failed to extract tarball info from latest version dist response.")?;
+    let pkg_tarball =
+        get_pkg_tarball_from_dist(pkg_tarball_name, pkg_dir, pkg_dist, tarball_limits)
+            .with_context(|| "Failed to extract tarball info from latest version dist response.")?;
 
     Ok(pkg_tarball)
 }
@@ -83,9 +109,9 @@ fn download_and_unpack_pkg_tarball(
     let pkg_contents = PkgContents::new(pkg_dir.to_owned(), &pkg_json, Some(pkg_tarball))
         .with_context(|| "Failed to create package contents with tarball.")?;
 
-    let pkg_contents = Rc::new(pkg_contents);
+    let pkg_contents = Arc::new(pkg_contents);
 
-    let pkg_entries = PkgEntries::new(&pkg_json, Rc::clone(&pkg_contents))
+    let pkg_entries = PkgEntries::new(&pkg_json, Arc::clone(&pkg_contents))
         .with_context(|| "Failed to create package entries.")?;
 
     Ok(Pkg::new(
@@ -99,14 +125,18 @@ fn download_and_unpack_pkg_tarball(
 
 fn get_pkg_json_from_tarball(pkg_tarball: &mut PkgTarball) -> Result<JsonValue> {
     let path = PathBuf::from("package.json");
-    let data = pkg_tarball.load_file_by_path(&path)?;
-    let data = String::from_utf8(data.unwrap())?;
+    let data = match pkg_tarball.load_file_by_path(&path)? {
+        Some(data) => data,
+        None => bail!("Package tarball has no package.json file."),
+    };
+
+    let data = String::from_utf8(data)?;
 
     Pkg::parse_config_as_json(data)
 }
 
-fn fetch_latest_pkg_info_for(pkg: &Pkg) -> Result<JsonValue> {
-    let request_url = &pkg.registry_url.join(&pkg.name)?;
+fn fetch_latest_pkg_info_for(pkg_name: &str, pkg_registry_url: &Url) -> Result<JsonValue> {
+    let request_url = &pkg_registry_url.join(pkg_name)?;
     let response = reqwest::blocking::get(request_url.to_string())?.error_for_status();
 
     if let Err(error) = response {
@@ -119,7 +149,12 @@ fn fetch_latest_pkg_info_for(pkg: &Pkg) -> Result<JsonValue> {
     Ok(response_body)
 }
 
-fn get_pkg_tarball_from_dist(name: String, dir: &PathBuf, dist: &JsonValue) -> Result<PkgTarball> {
+fn get_pkg_tarball_from_dist(
+    name: String,
+    dir: &PathBuf,
+    dist: &JsonValue,
+    limits: &TarballLimits,
+) -> Result<PkgTarball> {
     let tarball_url = &dist["tarball"];
     let tarball_checksum = &dist["integrity"];
 
@@ -146,5 +181,11 @@ fn get_pkg_tarball_from_dist(name: String, dir: &PathBuf, dist: &JsonValue) -> R
     let tarball_url = Url::parse(tarball_url.as_str().unwrap())?;
     let tarball_checksum = BASE_64_STANDARD.decode(tarball_hash_integrity)?;
 
-    PkgTarball::new(name, dir.to_owned(), tarball_url, tarball_checksum)
+    PkgTarball::new(
+        name,
+        dir.to_owned(),
+        tarball_url,
+        tarball_checksum,
+        limits.to_owned(),
+    )
 }
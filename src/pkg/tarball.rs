@@ -1,29 +1,127 @@
-use anyhow::{bail, Result};
+use crate::fs::path::normalize_relative_path;
+use anyhow::{bail, Context, Result};
 use flate2::bufread::GzDecoder;
 use hmac_sha512::Hash;
-use std::collections::HashSet;
-use std::fs;
-use std::io::Read;
-use std::path::PathBuf;
-use tar::{Archive, Entry};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use strum_macros::Display;
+use tar::{Archive, EntryType};
 use url::Url;
 
+/// Decompressed archives above this size are spilled to disk instead of being held in
+/// memory.
+const MAX_IN_MEMORY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The most archive data an entry may take besides its contents: its header block, up to
+/// a block of padding, and an extended header of similar size.
+const MAX_ENTRY_OVERHEAD: u64 = 4 * 512;
+
 pub struct PkgTarball {
     name: String,
     dir: PathBuf,
     source_url: Url,
     checksum: Vec<u8>,
-    data: Option<Vec<u8>>,
+    limits: TarballLimits,
+    storage: Option<TarballStorage>,
+    index: HashMap<PathBuf, TarballFile>,
 }
 
-impl PkgTarball {
-    pub fn new(name: String, dir: PathBuf, source_url: Url, checksum: Vec<u8>) -> Result<Self> {
-        let data = None;
+/// Bounds on the archives that are read, so that a malicious or broken package can't
+/// exhaust the memory or disk.
+#[derive(Clone)]
+pub struct TarballLimits {
+    pub max_entry_count: usize,
+    pub max_file_size: u64,
+    pub max_total_size: u64,
+}
+
+#[derive(Display, Debug)]
+pub enum TarballError {
+    #[strum(to_string = "Archive is malformed: {reason}")]
+    Malformed { reason: String },
+    #[strum(serialize = "Archive has no root directory")]
+    MissingRootDir,
+    #[strum(to_string = "Archive has more than one root directory: {first}, {second}")]
+    MultipleRootDirs { first: String, second: String },
+    #[strum(to_string = "Archive entry has an absolute path: {path}")]
+    AbsolutePath { path: String },
+    #[strum(to_string = "Archive entry escapes the package root: {path}")]
+    PathTraversal { path: String },
+    #[strum(to_string = "Archive link escapes the package root: {path} -> {target}")]
+    LinkEscape { path: String, target: String },
+    #[strum(to_string = "Archive has more than {limit} entries")]
+    TooManyEntries { limit: usize },
+    #[strum(to_string = "Archive file is larger than {limit} bytes: {path}")]
+    FileTooLarge { path: String, limit: u64 },
+    #[strum(to_string = "Archive files are larger than {limit} bytes in total")]
+    TotalSizeTooLarge { limit: u64 },
+}
+
+impl std::error::Error for TarballError {}
+
+/// An archive entry before its path is made relative to the package root.
+struct RawArchiveEntry {
+    path: PathBuf,
+    entry_type: EntryType,
+    link_name: Option<PathBuf>,
+    file: TarballFile,
+}
+
+/// Where the decompressed archive is kept once downloaded.
+enum TarballStorage {
+    Memory(Vec<u8>),
+    Disk(SpillFile),
+}
+
+/// A decompressed archive spilled to disk, which is removed once no longer used.
+struct SpillFile {
+    path: PathBuf,
+}
+
+/// Stores the decompressed archive as it is read, and spills it to disk once it outgrows
+/// the memory, so that its size is never trusted before the limits are checked.
+struct ArchiveSpooler<'path, Data: Read> {
+    data: Data,
+    spill_path: &'path Path,
+    max_in_memory_size: u64,
+    buffer: Vec<u8>,
+    spill_file: Option<(BufWriter<File>, SpillFile)>,
+}
+
+/// The position of a file's contents within the decompressed archive.
+#[derive(Clone)]
+pub struct TarballFile {
+    pub offset: u64,
+    pub size: u64,
+    pub mode: u32,
+}
+
+impl Default for TarballLimits {
+    fn default() -> Self {
+        Self {
+            max_entry_count: 100_000,
+            max_file_size: 512 * 1024 * 1024,
+            max_total_size: 2 * 1024 * 1024 * 1024,
+        }
+    }
+}
 
+impl PkgTarball {
+    pub fn new(
+        name: String,
+        dir: PathBuf,
+        source_url: Url,
+        checksum: Vec<u8>,
+        limits: TarballLimits,
+    ) -> Result<Self> {
         Ok(Self {
             source_url,
             checksum,
-            data,
+            limits,
+            storage: None,
+            index: HashMap::new(),
             name,
             dir,
         })
@@ -38,7 +136,7 @@ impl PkgTarball {
             if self.is_integrity_ok(&tarball_data) {
                 println!("Valid tarball exists on file system. Will use existing...");
 
-                return self.decode_and_store_data(tarball_data);
+                return self.decode_and_index_data(&tarball_data);
             }
 
             println!("Found existing tarball but integrity check failed. Will remove existing...");
@@ -67,93 +165,649 @@ impl PkgTarball {
         }
 
         fs::write(&tarball_path, &tarball_data)?;
-        self.decode_and_store_data(tarball_data_vec)
+        self.decode_and_index_data(&tarball_data_vec)
     }
 
-    pub fn get_files<ShouldInclude>(
-        &self,
-        should_include: Option<ShouldInclude>,
-    ) -> Result<HashSet<PathBuf>>
-    where
-        ShouldInclude: Fn(&Entry<&[u8]>) -> Result<bool>,
-    {
-        let mut files = HashSet::new();
+    pub fn get_files(&self) -> HashSet<PathBuf> {
+        self.index.keys().cloned().collect()
+    }
 
-        if let Some(data) = &self.data {
-            let mut archive = Archive::new(&data[..]);
+    pub fn get_file(&self, file_path: &Path) -> Option<&TarballFile> {
+        self.index.get(normalize_file_path(file_path))
+    }
 
-            for entry in archive.entries()? {
-                let entry = entry.unwrap();
+    pub fn load_file_by_path(&self, file_path: &Path) -> Result<Option<Vec<u8>>> {
+        let (storage, tarball_file) = match (&self.storage, self.get_file(file_path)) {
+            (Some(storage), Some(tarball_file)) => (storage, tarball_file),
+            _ => return Ok(None),
+        };
 
-                if let Some(should_include) = &should_include {
-                    if !should_include(&entry)? {
-                        continue;
-                    }
+        let mut buffer = vec![0; tarball_file.size as usize];
+
+        match storage {
+            TarballStorage::Memory(data) => {
+                let start = tarball_file.offset as usize;
+                let end = start + buffer.len();
+
+                match data.get(start..end) {
+                    Some(file_data) => buffer.copy_from_slice(file_data),
+                    None => bail!("File is out of the archive bounds: {}", file_path.display()),
+                }
+            }
+            TarballStorage::Disk(spill_file) => {
+                let mut archive_file = File::open(&spill_file.path)?;
+
+                archive_file.seek(SeekFrom::Start(tarball_file.offset))?;
+                archive_file.read_exact(&mut buffer)?;
+            }
+        }
+
+        Ok(Some(buffer))
+    }
+
+    /// Decompresses the archive, and indexes the position of each file within it so that
+    /// they can be loaded without scanning the whole archive again. The limits are
+    /// checked while decompressing, as the compressed size says little about the rest.
+    fn decode_and_index_data(&mut self, data: &[u8]) -> Result<()> {
+        let max_archive_size = self.limits.max_total_size.saturating_add(
+            (self.limits.max_entry_count as u64 + 1).saturating_mul(MAX_ENTRY_OVERHEAD),
+        );
+
+        let spill_path = self.path().with_extension("");
+        let decoder = GzDecoder::new(data).take(max_archive_size);
+        let mut spooler = ArchiveSpooler::new(decoder, &spill_path, MAX_IN_MEMORY_SIZE);
+
+        let index = match index_archive(&mut spooler, &self.limits) {
+            Ok(index) => index,
+            // The archive was cut short by the size bound, rather than truly malformed.
+            Err(_) if spooler.data.limit().eq(&0) => {
+                return Err(TarballError::TotalSizeTooLarge {
+                    limit: self.limits.max_total_size,
                 }
+                .into())
+            }
+            Err(error) => return Err(error),
+        };
+
+        self.storage = Some(spooler.into_storage()?);
+        self.index = index;
+
+        Ok(())
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(&self.name)
+    }
+
+    fn is_integrity_ok(&self, buffer: &Vec<u8>) -> bool {
+        let mut hash = Hash::new();
+
+        hash.update(buffer);
+        hash.finalize().eq(self.checksum.as_slice())
+    }
+}
+
+impl<'path, Data: Read> ArchiveSpooler<'path, Data> {
+    fn new(data: Data, spill_path: &'path Path, max_in_memory_size: u64) -> Self {
+        Self {
+            data,
+            spill_path,
+            max_in_memory_size,
+            buffer: Vec::new(),
+            spill_file: None,
+        }
+    }
+
+    fn store(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.spill_file.is_none()
+            && ((self.buffer.len() + data.len()) as u64).gt(&self.max_in_memory_size)
+        {
+            let spill_file = SpillFile {
+                path: self.spill_path.to_path_buf(),
+            };
+
+            let mut writer = BufWriter::new(File::create(&spill_file.path)?);
 
-                let entry_path = entry
-                    .header()
-                    .path()?
-                    .strip_prefix("package")?
-                    .to_path_buf();
+            writer.write_all(&self.buffer)?;
+            self.buffer = Vec::new();
+            self.spill_file = Some((writer, spill_file));
+        }
 
-                files.insert(entry_path);
+        match &mut self.spill_file {
+            Some((writer, _)) => writer.write_all(data),
+            None => {
+                self.buffer.extend_from_slice(data);
+                Ok(())
             }
         }
+    }
 
-        Ok(files)
+    fn into_storage(self) -> Result<TarballStorage> {
+        match self.spill_file {
+            Some((mut writer, spill_file)) => {
+                writer.flush().with_context(|| {
+                    format!(
+                        "Failed to write archive file: {}",
+                        spill_file.path.display()
+                    )
+                })?;
+
+                Ok(TarballStorage::Disk(spill_file))
+            }
+            None => Ok(TarballStorage::Memory(self.buffer)),
+        }
     }
+}
+
+impl<Data: Read> Read for ArchiveSpooler<'_, Data> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read_size = self.data.read(buffer)?;
 
-    pub fn load_file_by_path(&self, file_path: &PathBuf) -> Result<Option<Vec<u8>>> {
-        if let Some(data) = &self.data {
-            let mut archive = Archive::new(&data[..]);
+        self.store(&buffer[..read_size]).map_err(|error| {
+            io::Error::new(error.kind(), format!("Failed to spill archive: {error}"))
+        })?;
 
-            for entry in archive.entries()? {
-                let mut entry = entry.unwrap();
+        Ok(read_size)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
 
-                let entry_path = entry
-                    .header()
-                    .path()?
-                    .strip_prefix("package")?
-                    .to_path_buf();
+fn index_archive<Data: Read>(
+    data: Data,
+    limits: &TarballLimits,
+) -> Result<HashMap<PathBuf, TarballFile>> {
+    let raw_entries = read_archive_entries(data, limits)?;
+    let root_dir = get_archive_root_dir(&raw_entries)?;
+
+    let mut index = HashMap::new();
+    let mut links = Vec::new();
+
+    for raw_entry in raw_entries {
+        let entry_path = match raw_entry.path.strip_prefix(&root_dir) {
+            Ok(entry_path) if !entry_path.as_os_str().is_empty() => entry_path.to_path_buf(),
+            _ => continue,
+        };
+
+        match raw_entry.entry_type {
+            EntryType::Regular | EntryType::Continuous => {
+                index.insert(entry_path, raw_entry.file);
+            }
+            EntryType::Symlink | EntryType::Link => {
+                let link_name = raw_entry.link_name.unwrap_or_default();
 
-                let file_path = if file_path.starts_with("./") {
-                    file_path.strip_prefix("./")?
-                } else if file_path.starts_with("/") {
-                    file_path.strip_prefix("/")?
+                // Hard links are relative to the archive root, symbolic links to their
+                // own directory.
+                let link_target = if raw_entry.entry_type.is_hard_link() {
+                    link_name.strip_prefix(&root_dir).map(Path::to_path_buf)
                 } else {
-                    file_path
+                    Ok(entry_path
+                        .parent()
+                        .unwrap_or(Path::new(""))
+                        .join(&link_name))
                 };
 
-                if entry_path.eq(file_path) {
-                    let mut buffer = Vec::new();
-                    entry.read_to_end(&mut buffer)?;
-                    return Ok(Some(buffer));
+                let link_target = link_target
+                    .ok()
+                    .and_then(|link_target| normalize_relative_path(&link_target));
+
+                match link_target {
+                    Some(link_target) => links.push((entry_path, link_target)),
+                    None => {
+                        return Err(TarballError::LinkEscape {
+                            path: entry_path.display().to_string(),
+                            target: link_name.display().to_string(),
+                        }
+                        .into())
+                    }
                 }
             }
+            _ => {}
         }
+    }
 
-        Ok(None)
+    // Links to files of the package are readable through their target, links to
+    // anything else are left out.
+    for (link_path, link_target) in links {
+        if let Some(target_file) = index.get(&link_target).cloned() {
+            index.insert(link_path, target_file);
+        }
     }
 
-    fn decode_and_store_data(&mut self, data: Vec<u8>) -> Result<()> {
-        let mut buffer = Vec::new();
-        let mut decoder = GzDecoder::new(&data[..]);
+    Ok(index)
+}
+
+fn read_archive_entries<Data: Read>(
+    data: Data,
+    limits: &TarballLimits,
+) -> Result<Vec<RawArchiveEntry>> {
+    let mut raw_entries = Vec::new();
+    let mut total_size: u64 = 0;
+    let mut archive = Archive::new(data);
+
+    for entry in archive.entries().map_err(to_malformed_error)? {
+        let entry = entry.map_err(to_malformed_error)?;
+        let header = entry.header();
+
+        if raw_entries.len().ge(&limits.max_entry_count) {
+            return Err(TarballError::TooManyEntries {
+                limit: limits.max_entry_count,
+            }
+            .into());
+        }
+
+        let path = header.path().map_err(to_malformed_error)?.to_path_buf();
 
-        decoder.read_to_end(&mut buffer)?;
+        validate_archive_path(&path)?;
 
-        self.data = Some(buffer);
-        Ok(())
+        let size = entry.size();
+
+        if size.gt(&limits.max_file_size) {
+            return Err(TarballError::FileTooLarge {
+                path: path.display().to_string(),
+                limit: limits.max_file_size,
+            }
+            .into());
+        }
+
+        total_size += size;
+
+        if total_size.gt(&limits.max_total_size) {
+            return Err(TarballError::TotalSizeTooLarge {
+                limit: limits.max_total_size,
+            }
+            .into());
+        }
+
+        let link_name = header
+            .link_name()
+            .map_err(to_malformed_error)?
+            .map(|link_name| link_name.to_path_buf());
+
+        raw_entries.push(RawArchiveEntry {
+            path,
+            entry_type: header.entry_type(),
+            link_name,
+            file: TarballFile {
+                offset: entry.raw_file_position(),
+                size,
+                mode: header.mode().map_err(to_malformed_error)?,
+            },
+        });
     }
 
-    fn path(&self) -> PathBuf {
-        self.dir.join(&self.name)
+    Ok(raw_entries)
+}
+
+/// Packages are packed within a single directory, which npm names "package" but older
+/// or non-npm tools may name differently.
+fn get_archive_root_dir(raw_entries: &[RawArchiveEntry]) -> Result<PathBuf> {
+    let mut root_dir: Option<&Path> = None;
+
+    for raw_entry in raw_entries {
+        // Global pax headers don't describe a path of the package.
+        if raw_entry.entry_type.eq(&EntryType::XGlobalHeader) {
+            continue;
+        }
+
+        let entry_root_dir = match raw_entry.path.components().next() {
+            Some(Component::Normal(entry_root_dir)) => Path::new(entry_root_dir),
+            _ => continue,
+        };
+
+        let is_root_file =
+            raw_entry.path.components().count().eq(&1) && !raw_entry.entry_type.is_dir();
+
+        if is_root_file {
+            return Err(TarballError::MissingRootDir.into());
+        }
+
+        match root_dir {
+            None => root_dir = Some(entry_root_dir),
+            Some(root_dir) if root_dir.ne(entry_root_dir) => {
+                return Err(TarballError::MultipleRootDirs {
+                    first: root_dir.display().to_string(),
+                    second: entry_root_dir.display().to_string(),
+                }
+                .into())
+            }
+            _ => {}
+        }
     }
 
-    fn is_integrity_ok(&self, buffer: &Vec<u8>) -> bool {
-        let mut hash = Hash::new();
+    match root_dir {
+        Some(root_dir) => Ok(root_dir.to_path_buf()),
+        None => Err(TarballError::MissingRootDir.into()),
+    }
+}
 
-        hash.update(buffer);
-        hash.finalize().eq(self.checksum.as_slice())
+fn validate_archive_path(path: &Path) -> Result<()> {
+    for component in path.components() {
+        match component {
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(TarballError::AbsolutePath {
+                    path: path.display().to_string(),
+                }
+                .into())
+            }
+            Component::ParentDir => {
+                return Err(TarballError::PathTraversal {
+                    path: path.display().to_string(),
+                }
+                .into())
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn to_malformed_error(error: io::Error) -> TarballError {
+    TarballError::Malformed {
+        reason: error.to_string(),
+    }
+}
+
+fn normalize_file_path(file_path: &Path) -> &Path {
+    file_path
+        .strip_prefix("./")
+        .or_else(|_| file_path.strip_prefix("/"))
+        .unwrap_or(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tar::{Builder, Header};
+
+    /// Builds an archive of the given entries, given as a path, type, link name and
+    /// contents. Paths are written as-is, so that invalid ones can be tested.
+    fn build_archive(entries: &[(&str, EntryType, &str, &[u8])]) -> Vec<u8> {
+        let mut builder = Builder::new(Vec::new());
+
+        for (path, entry_type, link_name, contents) in entries {
+            let mut header = Header::new_gnu();
+            let name = &mut header.as_gnu_mut().unwrap().name;
+
+            name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_entry_type(*entry_type);
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+
+            if !link_name.is_empty() {
+                header.set_link_name(link_name).unwrap();
+            }
+
+            header.set_cksum();
+            builder.append(&header, *contents).unwrap();
+        }
+
+        builder.into_inner().unwrap()
+    }
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn get_error(result: Result<impl Sized>) -> TarballError {
+        match result {
+            Ok(_) => panic!("expected a tarball error"),
+            Err(error) => error.downcast::<TarballError>().unwrap(),
+        }
+    }
+
+    fn get_sorted_files(index: &HashMap<PathBuf, TarballFile>) -> Vec<String> {
+        let mut files: Vec<String> = index
+            .keys()
+            .map(|path| path.display().to_string())
+            .collect();
+
+        files.sort();
+        files
+    }
+
+    fn get_test_tarball(fixture_name: &str, limits: TarballLimits) -> PkgTarball {
+        PkgTarball::new(
+            format!(
+                "breakpoint-tarball-{fixture_name}-{}.tgz",
+                std::process::id()
+            ),
+            std::env::temp_dir(),
+            Url::parse("https://registry.npmjs.org/").unwrap(),
+            Vec::new(),
+            limits,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn files_are_indexed_relative_to_a_custom_root_dir() {
+        let archive = build_archive(&[
+            ("node/", EntryType::Directory, "", b""),
+            ("node/package.json", EntryType::Regular, "", b"{}"),
+            ("node/lib/index.js", EntryType::Regular, "", b"export {};"),
+        ]);
+
+        let index = index_archive(&archive[..], &TarballLimits::default()).unwrap();
+
+        assert_eq!(get_sorted_files(&index), ["lib/index.js", "package.json"]);
+        assert_eq!(index[Path::new("package.json")].size, 2);
+    }
+
+    #[test]
+    fn root_files_and_multiple_root_dirs_are_rejected() {
+        let root_file = build_archive(&[("package.json", EntryType::Regular, "", b"{}")]);
+        let error = get_error(index_archive(&root_file[..], &TarballLimits::default()));
+
+        assert!(matches!(error, TarballError::MissingRootDir));
+
+        let two_roots = build_archive(&[
+            ("package/index.js", EntryType::Regular, "", b""),
+            ("other/index.js", EntryType::Regular, "", b""),
+        ]);
+
+        let error = get_error(index_archive(&two_roots[..], &TarballLimits::default()));
+
+        assert!(matches!(error, TarballError::MultipleRootDirs { .. }));
+    }
+
+    #[test]
+    fn absolute_and_traversing_paths_are_rejected() {
+        let absolute = build_archive(&[("/package/index.js", EntryType::Regular, "", b"")]);
+        let error = get_error(index_archive(&absolute[..], &TarballLimits::default()));
+
+        assert!(matches!(error, TarballError::AbsolutePath { .. }));
+
+        let traversing = build_archive(&[
+            ("package/index.js", EntryType::Regular, "", b""),
+            ("package/../../evil.js", EntryType::Regular, "", b""),
+        ]);
+
+        let error = get_error(index_archive(&traversing[..], &TarballLimits::default()));
+
+        assert!(matches!(error, TarballError::PathTraversal { .. }));
+    }
+
+    #[test]
+    fn links_resolve_to_files_of_the_package() {
+        let archive = build_archive(&[
+            (
+                "package/lib/index.js",
+                EntryType::Regular,
+                "",
+                b"export {};",
+            ),
+            ("package/index.js", EntryType::Symlink, "lib/index.js", b""),
+            (
+                "package/lib/main.js",
+                EntryType::Link,
+                "package/lib/index.js",
+                b"",
+            ),
+            (
+                "package/missing.js",
+                EntryType::Symlink,
+                "lib/missing.js",
+                b"",
+            ),
+        ]);
+
+        let index = index_archive(&archive[..], &TarballLimits::default()).unwrap();
+
+        assert_eq!(
+            get_sorted_files(&index),
+            ["index.js", "lib/index.js", "lib/main.js"]
+        );
+
+        assert_eq!(
+            index[Path::new("index.js")].offset,
+            index[Path::new("lib/index.js")].offset
+        );
+    }
+
+    #[test]
+    fn links_escaping_the_package_root_are_rejected() {
+        let symlink = build_archive(&[
+            ("package/index.js", EntryType::Regular, "", b""),
+            (
+                "package/lib/evil.js",
+                EntryType::Symlink,
+                "../../evil.js",
+                b"",
+            ),
+        ]);
+
+        let error = get_error(index_archive(&symlink[..], &TarballLimits::default()));
+
+        assert!(matches!(error, TarballError::LinkEscape { .. }));
+
+        let hard_link = build_archive(&[
+            ("package/index.js", EntryType::Regular, "", b""),
+            ("package/evil.js", EntryType::Link, "other/evil.js", b""),
+        ]);
+
+        let error = get_error(index_archive(&hard_link[..], &TarballLimits::default()));
+
+        assert!(matches!(error, TarballError::LinkEscape { .. }));
+    }
+
+    #[test]
+    fn entry_count_limit_is_enforced() {
+        let archive = build_archive(&[
+            ("package/a.js", EntryType::Regular, "", b""),
+            ("package/b.js", EntryType::Regular, "", b""),
+            ("package/c.js", EntryType::Regular, "", b""),
+        ]);
+
+        let limits = TarballLimits {
+            max_entry_count: 2,
+            ..TarballLimits::default()
+        };
+
+        let error = get_error(index_archive(&archive[..], &limits));
+
+        assert!(matches!(error, TarballError::TooManyEntries { limit: 2 }));
+    }
+
+    #[test]
+    fn file_size_limit_is_enforced() {
+        let archive = build_archive(&[("package/a.js", EntryType::Regular, "", &[b'a'; 100])]);
+
+        let limits = TarballLimits {
+            max_file_size: 99,
+            ..TarballLimits::default()
+        };
+
+        let error = get_error(index_archive(&archive[..], &limits));
+
+        assert!(matches!(
+            error,
+            TarballError::FileTooLarge { limit: 99, .. }
+        ));
+    }
+
+    #[test]
+    fn total_size_limit_is_enforced() {
+        let archive = build_archive(&[
+            ("package/a.js", EntryType::Regular, "", &[b'a'; 60]),
+            ("package/b.js", EntryType::Regular, "", &[b'b'; 60]),
+        ]);
+
+        let limits = TarballLimits {
+            max_total_size: 100,
+            ..TarballLimits::default()
+        };
+
+        let error = get_error(index_archive(&archive[..], &limits));
+
+        assert!(matches!(
+            error,
+            TarballError::TotalSizeTooLarge { limit: 100 }
+        ));
+    }
+
+    #[test]
+    fn oversized_headers_stop_decompression_at_the_size_bound() {
+        // A long name entry is read whole by the archive reader, so only the bound on the
+        // decompressed size keeps it from filling the memory.
+        let archive = build_archive(&[(
+            "././@LongLink",
+            EntryType::GNULongName,
+            "",
+            &vec![b'a'; 1024 * 1024],
+        )]);
+
+        let limits = TarballLimits {
+            max_entry_count: 10,
+            max_total_size: 1024,
+            ..TarballLimits::default()
+        };
+
+        let mut tarball = get_test_tarball("bomb", limits);
+        let error = get_error(tarball.decode_and_index_data(&compress(&archive)));
+
+        assert!(matches!(
+            error,
+            TarballError::TotalSizeTooLarge { limit: 1024 }
+        ));
+        assert!(tarball.storage.is_none());
+    }
+
+    #[test]
+    fn large_archives_are_spilled_to_disk_and_removed() {
+        let archive = build_archive(&[("package/a.js", EntryType::Regular, "", &[b'a'; 4096])]);
+        let spill_path = get_test_tarball("spill", TarballLimits::default())
+            .path()
+            .with_extension("");
+
+        let mut spooler = ArchiveSpooler::new(&archive[..], &spill_path, 1024);
+        let index = index_archive(&mut spooler, &TarballLimits::default()).unwrap();
+        let storage = spooler.into_storage().unwrap();
+
+        assert!(spill_path.is_file());
+
+        let tarball = PkgTarball {
+            storage: Some(storage),
+            index,
+            ..get_test_tarball("spill", TarballLimits::default())
+        };
+
+        let file_data = tarball.load_file_by_path(Path::new("a.js")).unwrap();
+
+        assert_eq!(file_data, Some(vec![b'a'; 4096]));
+
+        drop(tarball);
+
+        assert!(!spill_path.exists());
     }
 }
@@ -0,0 +1,678 @@
+use anyhow::{bail, Context, Result};
+use json::JsonValue;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+/// TypeScript releases that types are resolved for. Only releases supporting the
+/// node16 module resolution (4.7 and later) are relevant.
+const TS_RELEASES: [(u32, u32); 14] = [
+    (4, 7),
+    (4, 8),
+    (4, 9),
+    (5, 0),
+    (5, 1),
+    (5, 2),
+    (5, 3),
+    (5, 4),
+    (5, 5),
+    (5, 6),
+    (5, 7),
+    (5, 8),
+    (5, 9),
+    (6, 0),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TsVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TsVersionOperator {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+}
+
+/// A semver range (e.g. ">=4.2 <5 || 5.4") as used by `typesVersions` keys, `types@`
+/// export conditions and the `--ts-versions` option.
+#[derive(Clone, Debug)]
+pub struct TsVersionRange {
+    source: String,
+    comparator_sets: Vec<Vec<(TsVersionOperator, TsVersion)>>,
+}
+
+#[derive(Display, EnumIter, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TsResolutionMode {
+    #[strum(serialize = "node16 (import)")]
+    Node16Import,
+    #[strum(serialize = "node16 (require)")]
+    Node16Require,
+    #[strum(serialize = "bundler")]
+    Bundler,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PkgTypesResolution {
+    /// The declaration file TypeScript would load, if any.
+    pub path: Option<PathBuf>,
+    /// The `typesVersions` range that redirected the resolution, if any.
+    pub types_versions_range: Option<String>,
+}
+
+pub struct PkgTypesEntry {
+    pub subpath: String,
+    pub resolutions: BTreeMap<(TsResolutionMode, TsVersion), PkgTypesResolution>,
+}
+
+impl TsVersion {
+    pub fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    pub fn releases() -> Vec<TsVersion> {
+        TS_RELEASES
+            .iter()
+            .map(|(major, minor)| TsVersion::new(*major, *minor))
+            .collect()
+    }
+
+    /// Parses a (possibly partial) version, returning whether the minor was given.
+    fn parse(value: &str) -> Result<(Self, bool)> {
+        let mut parts = value.trim_start_matches('v').split('.');
+
+        let major = match parts.next() {
+            Some(major) => major
+                .parse::<u32>()
+                .with_context(|| format!("Invalid TypeScript version: {value}"))?,
+            None => bail!("Invalid TypeScript version: {value}"),
+        };
+
+        let minor = match parts.next() {
+            Some("x") | Some("*") | None => None,
+            Some(minor) => Some(
+                minor
+                    .parse::<u32>()
+                    .with_context(|| format!("Invalid TypeScript version: {value}"))?,
+            ),
+        };
+
+        Ok((Self::new(major, minor.unwrap_or(0)), minor.is_some()))
+    }
+}
+
+impl Display for TsVersion {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl TsVersionRange {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut comparator_sets = Vec::new();
+
+        for comparator_set in source.split("||") {
+            let mut comparators = Vec::new();
+
+            for comparator in comparator_set.split_whitespace() {
+                Self::parse_comparator(comparator, &mut comparators)
+                    .with_context(|| format!("Invalid TypeScript version range: {source}"))?;
+            }
+
+            comparator_sets.push(comparators);
+        }
+
+        Ok(Self {
+            source: source.trim().to_string(),
+            comparator_sets,
+        })
+    }
+
+    pub fn matches(&self, version: &TsVersion) -> bool {
+        self.comparator_sets.iter().any(|comparators| {
+            comparators.iter().all(|(operator, bound)| match operator {
+                TsVersionOperator::Eq => version.eq(bound),
+                TsVersionOperator::Gt => version.gt(bound),
+                TsVersionOperator::Gte => version.ge(bound),
+                TsVersionOperator::Lt => version.lt(bound),
+            })
+        })
+    }
+
+    fn parse_comparator(
+        comparator: &str,
+        buffer: &mut Vec<(TsVersionOperator, TsVersion)>,
+    ) -> Result<()> {
+        if comparator.eq("*") || comparator.eq("x") {
+            return Ok(());
+        }
+
+        let (operator, value) = if let Some(value) = comparator.strip_prefix(">=") {
+            (">=", value)
+        } else if let Some(value) = comparator.strip_prefix("<=") {
+            ("<=", value)
+        } else if let Some(value) = comparator.strip_prefix('>') {
+            (">", value)
+        } else if let Some(value) = comparator.strip_prefix('<') {
+            ("<", value)
+        } else if let Some(value) = comparator.strip_prefix('=') {
+            ("=", value)
+        } else if let Some(value) = comparator.strip_prefix('~') {
+            ("~", value)
+        } else if let Some(value) = comparator.strip_prefix('^') {
+            ("^", value)
+        } else {
+            ("", comparator)
+        };
+
+        let (version, has_minor) = TsVersion::parse(value)?;
+        let next_major = TsVersion::new(version.major + 1, 0);
+        let next_minor = TsVersion::new(version.major, version.minor + 1);
+
+        // Partial versions (e.g. "4") cover every minor release of that major.
+        let upper_bound = if has_minor { next_minor } else { next_major };
+
+        match operator {
+            ">=" => buffer.push((TsVersionOperator::Gte, version)),
+            ">" if has_minor => buffer.push((TsVersionOperator::Gt, version)),
+            ">" => buffer.push((TsVersionOperator::Gte, next_major)),
+            "<=" => buffer.push((TsVersionOperator::Lt, upper_bound)),
+            "<" => buffer.push((TsVersionOperator::Lt, version)),
+            "^" => {
+                buffer.push((TsVersionOperator::Gte, version));
+                buffer.push((TsVersionOperator::Lt, next_major));
+            }
+            _ if has_minor => buffer.push((TsVersionOperator::Eq, version)),
+            _ => {
+                buffer.push((TsVersionOperator::Gte, version));
+                buffer.push((TsVersionOperator::Lt, next_major));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for TsVersionRange {
+    fn default() -> Self {
+        Self::parse("*").unwrap()
+    }
+}
+
+impl Display for TsVersionRange {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}", self.source)
+    }
+}
+
+impl TsResolutionMode {
+    pub fn is_supported_by(&self, version: &TsVersion) -> bool {
+        match self {
+            Self::Node16Import | Self::Node16Require => version.ge(&TsVersion::new(4, 7)),
+            Self::Bundler => version.ge(&TsVersion::new(5, 0)),
+        }
+    }
+
+    fn conditions(&self) -> &[&str] {
+        match self {
+            Self::Node16Import => &["types", "node", "import"],
+            Self::Node16Require => &["types", "node", "require"],
+            Self::Bundler => &["types", "import"],
+        }
+    }
+}
+
+/// Resolves the declaration files TypeScript would load for each public subpath of a
+/// package, for every supported module resolution mode and TypeScript release.
+pub fn resolve_types_entries(
+    pkg_json: &JsonValue,
+    pkg_files: &HashSet<PathBuf>,
+) -> Result<BTreeMap<String, PkgTypesEntry>> {
+    let mut entries = BTreeMap::new();
+
+    for subpath in get_public_subpaths(pkg_json) {
+        let mut resolutions = BTreeMap::new();
+
+        for mode in TsResolutionMode::iter() {
+            for version in TsVersion::releases() {
+                if !mode.is_supported_by(&version) {
+                    continue;
+                }
+
+                let resolution =
+                    resolve_subpath_types(pkg_json, pkg_files, &subpath, &mode, &version)
+                        .with_context(|| {
+                            format!("Failed to resolve types for subpath '{subpath}' with TypeScript {version} ({mode}).")
+                        })?;
+
+                resolutions.insert((mode, version), resolution);
+            }
+        }
+
+        entries.insert(
+            subpath.to_owned(),
+            PkgTypesEntry {
+                subpath,
+                resolutions,
+            },
+        );
+    }
+
+    Ok(entries)
+}
+
+fn get_public_subpaths(pkg_json: &JsonValue) -> Vec<String> {
+    let exports = &pkg_json["exports"];
+
+    if exports.is_null() {
+        let mut subpaths = vec![String::from(".")];
+
+        // Without exports, typesVersions can expose explicit (non-pattern) subpaths.
+        for (_, mappings) in pkg_json["typesVersions"].entries() {
+            for (pattern, _) in mappings.entries() {
+                let subpath = format!("./{pattern}");
+
+                if !pattern.contains('*') && !subpaths.contains(&subpath) {
+                    subpaths.push(subpath);
+                }
+            }
+        }
+
+        return subpaths;
+    }
+
+    let is_subpath_map =
+        exports.is_object() && exports.entries().any(|(key, _)| key.starts_with('.'));
+
+    if !is_subpath_map {
+        return vec![String::from(".")];
+    }
+
+    exports
+        .entries()
+        .filter(|(subpath, target)| !subpath.contains('*') && !target.is_null())
+        .map(|(subpath, _)| subpath.to_string())
+        .collect()
+}
+
+fn resolve_subpath_types(
+    pkg_json: &JsonValue,
+    pkg_files: &HashSet<PathBuf>,
+    subpath: &str,
+    mode: &TsResolutionMode,
+    version: &TsVersion,
+) -> Result<PkgTypesResolution> {
+    let exports = &pkg_json["exports"];
+
+    // When exports are defined they are the only source of truth, and typesVersions
+    // is not consulted.
+    if !exports.is_null() {
+        let is_subpath_map =
+            exports.is_object() && exports.entries().any(|(key, _)| key.starts_with('.'));
+
+        let target = if is_subpath_map {
+            &exports[subpath]
+        } else {
+            exports
+        };
+
+        let path = resolve_exports_target(target, mode, version)?
+            .and_then(|target_path| find_declaration_file_for(pkg_files, &target_path));
+
+        return Ok(PkgTypesResolution {
+            path,
+            types_versions_range: None,
+        });
+    }
+
+    let (types_versions_range, mappings) = get_types_versions_mappings_for(pkg_json, version)?;
+
+    let module_path = if subpath.eq(".") {
+        let types_field = if pkg_json["types"].is_string() {
+            &pkg_json["types"]
+        } else {
+            &pkg_json["typings"]
+        };
+
+        if types_field.is_string() {
+            types_field.to_string()
+        } else if pkg_json["main"].is_string() {
+            pkg_json["main"].to_string()
+        } else {
+            String::from("index.js")
+        }
+    } else {
+        subpath.trim_start_matches("./").to_string()
+    };
+
+    let module_path = normalize_pkg_path(Path::new(&module_path));
+
+    if let Some(mappings) = mappings {
+        if let Some(path) = apply_types_versions_mappings(pkg_files, mappings, &module_path) {
+            return Ok(PkgTypesResolution {
+                path: Some(path),
+                types_versions_range,
+            });
+        }
+    }
+
+    // The matching typesVersions range is kept even when none of its mappings apply, as
+    // it still determines which TypeScript versions the package declares support for.
+
+    let path = find_declaration_file_for(pkg_files, &module_path).or_else(|| {
+        if subpath.eq(".") {
+            find_declaration_file_for(pkg_files, Path::new("index.d.ts"))
+        } else {
+            None
+        }
+    });
+
+    Ok(PkgTypesResolution {
+        path,
+        types_versions_range,
+    })
+}
+
+fn resolve_exports_target(
+    target: &JsonValue,
+    mode: &TsResolutionMode,
+    version: &TsVersion,
+) -> Result<Option<PathBuf>> {
+    if target.is_string() {
+        let target = target.to_string();
+
+        // Subpath patterns are not resolved to concrete files.
+        if target.contains('*') {
+            return Ok(None);
+        }
+
+        return Ok(Some(normalize_pkg_path(Path::new(&target))));
+    }
+
+    if target.is_array() {
+        for fallback_target in target.members() {
+            if let Some(path) = resolve_exports_target(fallback_target, mode, version)? {
+                return Ok(Some(path));
+            }
+        }
+
+        return Ok(None);
+    }
+
+    if !target.is_object() {
+        return Ok(None);
+    }
+
+    for (condition, nested_target) in target.entries() {
+        let is_matching_condition = if let Some(range) = condition.strip_prefix("types@") {
+            TsVersionRange::parse(range)?.matches(version)
+        } else {
+            condition.eq("default") || mode.conditions().contains(&condition)
+        };
+
+        if !is_matching_condition {
+            continue;
+        }
+
+        if let Some(path) = resolve_exports_target(nested_target, mode, version)? {
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None)
+}
+
+fn get_types_versions_mappings_for<'pkg>(
+    pkg_json: &'pkg JsonValue,
+    version: &TsVersion,
+) -> Result<(Option<String>, Option<&'pkg JsonValue>)> {
+    // TypeScript uses the first range that matches, in declaration order.
+    for (range, mappings) in pkg_json["typesVersions"].entries() {
+        if TsVersionRange::parse(range)?.matches(version) {
+            return Ok((Some(range.to_string()), Some(mappings)));
+        }
+    }
+
+    Ok((None, None))
+}
+
+fn apply_types_versions_mappings(
+    pkg_files: &HashSet<PathBuf>,
+    mappings: &JsonValue,
+    module_path: &Path,
+) -> Option<PathBuf> {
+    let module_path = module_path.to_str()?;
+
+    for (pattern, substitutions) in mappings.entries() {
+        let captured = match pattern.split_once('*') {
+            Some((prefix, suffix)) => module_path
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix(suffix)),
+            None if pattern.eq(module_path) => Some(""),
+            None => None,
+        };
+
+        let captured = match captured {
+            Some(captured) => captured,
+            None => continue,
+        };
+
+        for substitution in substitutions.members() {
+            let substitution = substitution.to_string().replacen('*', captured, 1);
+            let substitution = normalize_pkg_path(Path::new(&substitution));
+
+            if let Some(path) = find_declaration_file_for(pkg_files, &substitution) {
+                return Some(path);
+            }
+        }
+
+        // Only the first matching pattern is applied.
+        return None;
+    }
+
+    None
+}
+
+/// Finds the declaration file TypeScript would load for a module path, taking the
+/// declaration file extension substitutions into account.
+fn find_declaration_file_for(pkg_files: &HashSet<PathBuf>, path: &Path) -> Option<PathBuf> {
+    let path_str = path.to_str()?;
+
+    let candidates: Vec<String> = if path_str.ends_with(".d.ts")
+        || path_str.ends_with(".d.mts")
+        || path_str.ends_with(".d.cts")
+    {
+        vec![path_str.into()]
+    } else if let Some(stem) = path_str.strip_suffix(".mjs") {
+        vec![format!("{stem}.d.mts")]
+    } else if let Some(stem) = path_str.strip_suffix(".cjs") {
+        vec![format!("{stem}.d.cts")]
+    } else if let Some(stem) = path_str.strip_suffix(".js") {
+        vec![format!("{stem}.d.ts")]
+    } else if let Some(stem) = path_str.strip_suffix(".ts") {
+        vec![format!("{stem}.d.ts"), path_str.into()]
+    } else {
+        vec![format!("{path_str}.d.ts"), format!("{path_str}/index.d.ts")]
+    };
+
+    candidates
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|candidate| pkg_files.contains(candidate))
+}
+
+fn normalize_pkg_path(path: &Path) -> PathBuf {
+    path.strip_prefix("./").unwrap_or(path).to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_matching_releases(range: &str) -> Vec<String> {
+        let range = TsVersionRange::parse(range).unwrap();
+
+        TsVersion::releases()
+            .iter()
+            .filter(|version| range.matches(version))
+            .map(|version| version.to_string())
+            .collect()
+    }
+
+    fn resolve(
+        pkg_json: &str,
+        pkg_files: &[&str],
+        subpath: &str,
+        mode: TsResolutionMode,
+        version: (u32, u32),
+    ) -> (Option<String>, Option<String>) {
+        let pkg_json = json::parse(pkg_json).unwrap();
+        let pkg_files = pkg_files.iter().map(PathBuf::from).collect();
+        let version = TsVersion::new(version.0, version.1);
+
+        let resolution =
+            resolve_subpath_types(&pkg_json, &pkg_files, subpath, &mode, &version).unwrap();
+
+        (
+            resolution.path.map(|path| path.display().to_string()),
+            resolution.types_versions_range,
+        )
+    }
+
+    #[test]
+    fn wildcard_ranges_match_every_release() {
+        assert_eq!(get_matching_releases("*").len(), TS_RELEASES.len());
+        assert_eq!(get_matching_releases("x").len(), TS_RELEASES.len());
+        assert_eq!(get_matching_releases("4.x"), ["4.7", "4.8", "4.9"]);
+        assert_eq!(get_matching_releases("6.*"), ["6.0"]);
+    }
+
+    #[test]
+    fn comparator_sets_are_alternatives() {
+        assert_eq!(
+            get_matching_releases(">=4.8 <5 || 5.4"),
+            ["4.8", "4.9", "5.4"]
+        );
+    }
+
+    #[test]
+    fn partial_versions_cover_the_whole_major() {
+        assert_eq!(get_matching_releases("5").len(), 10);
+        assert_eq!(get_matching_releases(">5"), ["6.0"]);
+        assert_eq!(get_matching_releases("<=4"), ["4.7", "4.8", "4.9"]);
+        assert_eq!(
+            get_matching_releases("<=5.1"),
+            ["4.7", "4.8", "4.9", "5.0", "5.1"]
+        );
+        assert_eq!(get_matching_releases(">5.8"), ["5.9", "6.0"]);
+        assert_eq!(get_matching_releases("^5.8"), ["5.8", "5.9"]);
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert!(TsVersionRange::parse("latest").is_err());
+        assert!(TsVersionRange::parse(">=4.y").is_err());
+        assert_eq!(
+            TsVersionRange::parse(" >=5.0 ").unwrap().to_string(),
+            ">=5.0"
+        );
+    }
+
+    #[test]
+    fn exports_conditions_are_resolved_per_mode() {
+        let pkg_json = r#"{"exports": {".": {
+            "import": {"types": "./index.d.mts"},
+            "require": {"types": "./index.d.cts"}
+        }}}"#;
+
+        let pkg_files = ["index.d.mts", "index.d.cts"];
+
+        for (mode, expected_path) in [
+            (TsResolutionMode::Node16Import, "index.d.mts"),
+            (TsResolutionMode::Node16Require, "index.d.cts"),
+            (TsResolutionMode::Bundler, "index.d.mts"),
+        ] {
+            assert_eq!(
+                resolve(pkg_json, &pkg_files, ".", mode, (5, 0)),
+                (Some(expected_path.into()), None)
+            );
+        }
+    }
+
+    #[test]
+    fn exports_fallback_arrays_use_the_first_matching_target() {
+        let pkg_json = r#"{"exports": {
+            ".": [{"types@<5.0": "./ts4/index.d.ts"}, "./index.js"],
+            "./feature": "./dist/feature/*.js"
+        }}"#;
+
+        let pkg_files = ["ts4/index.d.ts", "index.d.ts"];
+        let mode = TsResolutionMode::Node16Import;
+
+        assert_eq!(
+            resolve(pkg_json, &pkg_files, ".", mode, (4, 9)),
+            (Some("ts4/index.d.ts".into()), None)
+        );
+
+        assert_eq!(
+            resolve(pkg_json, &pkg_files, ".", mode, (5, 0)),
+            (Some("index.d.ts".into()), None)
+        );
+
+        assert_eq!(
+            resolve(pkg_json, &pkg_files, "./feature", mode, (5, 0)),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn types_versions_wildcards_redirect_matching_releases() {
+        let pkg_json = r#"{
+            "types": "index.d.ts",
+            "typesVersions": {"<5.0": {"*": ["missing/*", "ts4/*"]}}
+        }"#;
+
+        let pkg_files = ["index.d.ts", "ts4/index.d.ts", "ts4/extra.d.ts"];
+        let mode = TsResolutionMode::Node16Require;
+
+        assert_eq!(
+            resolve(pkg_json, &pkg_files, ".", mode, (4, 8)),
+            (Some("ts4/index.d.ts".into()), Some("<5.0".into()))
+        );
+
+        assert_eq!(
+            resolve(pkg_json, &pkg_files, ".", mode, (5, 0)),
+            (Some("index.d.ts".into()), None)
+        );
+
+        assert_eq!(
+            resolve(pkg_json, &pkg_files, "./extra", mode, (4, 8)),
+            (Some("ts4/extra.d.ts".into()), Some("<5.0".into()))
+        );
+    }
+
+    #[test]
+    fn unmapped_types_versions_fall_back_to_the_main_entry() {
+        let pkg_json = r#"{
+            "main": "./lib/main.js",
+            "typesVersions": {">=5.0": {"other": ["other.d.ts"]}}
+        }"#;
+
+        let mode = TsResolutionMode::Bundler;
+
+        assert_eq!(
+            resolve(pkg_json, &["lib/main.d.ts"], ".", mode, (5, 4)),
+            (Some("lib/main.d.ts".into()), Some(">=5.0".into()))
+        );
+
+        assert_eq!(
+            resolve(pkg_json, &["index.d.ts"], ".", mode, (5, 4)),
+            (Some("index.d.ts".into()), Some(">=5.0".into()))
+        );
+    }
+}